@@ -1,19 +1,33 @@
 use core::ops;
-use std::cmp::{Eq, PartialEq, PartialOrd};
-// use std::cmp::{Ord, Ordering};
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-use crate::{Price, Worth};
+use crate::{
+    check_f64, check_num, deserialize_num, num_cmp, Num, NumError, Price, Worth, NUM_ZERO,
+};
 
 // Why not Qty as more "loud" maybe? To separate "qty" and "quote".
 // So, Amount
 /// Amount (quantity) of base currency.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialOrd, PartialEq)]
-pub struct Amount(pub f64);
+#[derive(Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct Amount(pub Num);
 
 impl Eq for Amount {}
 
+// for min, max, sort and BTreeMap keys
+impl Ord for Amount {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        num_cmp(self.0, rhs.0)
+    }
+}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
 impl From<(Price, Worth)> for Amount {
     fn from((p, w): (Price, Worth)) -> Self {
         Self::from_pw(p, w)
@@ -21,11 +35,41 @@ impl From<(Price, Worth)> for Amount {
 }
 
 impl Amount {
+    pub const ZERO: Self = Self(NUM_ZERO);
+
+    /// Checked constructor: NaN, inf and negative values are not allowed
+    pub fn new(v: Num) -> Result<Self, NumError> {
+        check_num(v).map(Self)
+    }
+
     pub fn from_pw(p: Price, w: Worth) -> Self {
         Self(w.0 / p.0)
     }
 }
 
+impl From<Amount> for Num {
+    fn from(v: Amount) -> Num {
+        v.0
+    }
+}
+
+impl TryFrom<f64> for Amount {
+    type Error = NumError;
+
+    fn try_from(v: f64) -> Result<Self, NumError> {
+        check_f64(v).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Self::new(deserialize_num(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
 impl ops::Sub<Amount> for Amount {
     type Output = Self;
     fn sub(self, rhs: Amount) -> Self {
@@ -52,21 +96,21 @@ impl ops::SubAssign<Amount> for Amount {
     }
 }
 
-impl ops::Mul<f64> for Amount {
+impl ops::Mul<Num> for Amount {
     type Output = Self;
-    fn mul(self, rhs: f64) -> Self {
+    fn mul(self, rhs: Num) -> Self {
         Self(self.0 * rhs)
     }
 }
 
-impl ops::Div<f64> for Amount {
+impl ops::Div<Num> for Amount {
     type Output = Self;
-    fn div(self, rhs: f64) -> Self {
+    fn div(self, rhs: Num) -> Self {
         Self(self.0 / rhs)
     }
 }
 
-impl ops::Div<Amount> for f64 {
+impl ops::Div<Amount> for Num {
     type Output = Amount;
     fn div(self, rhs: Amount) -> Amount {
         Amount(self / rhs.0)
@@ -74,44 +118,72 @@ impl ops::Div<Amount> for f64 {
 }
 
 impl ops::Div<Amount> for Amount {
-    type Output = f64;
-    fn div(self, rhs: Amount) -> f64 {
+    type Output = Num;
+    fn div(self, rhs: Amount) -> Num {
         self.0 / rhs.0
     }
 }
 
-// for min, max
-// https://www.reddit.com/r/rust/comments/29kia3/no_ord_for_f32/
-// impl Ord for Amount {
-//     fn cmp(&self, rhs: &Self) -> Ordering {
-//         self.0.partial_cmp(&rhs.0).unwrap_or(Ordering::Equal)
-//     }
-// }
-
 #[cfg(test)]
 mod tests {
     use serde_json::json;
 
     use super::*;
+    use crate::n;
 
     #[test]
+    #[cfg(not(feature = "decimal"))]
     fn test_amount_to_json() {
         // https://serde.rs/derive.html
-        let a = Amount(1.0);
+        let a = Amount(n(1.0));
         let v = json!(a);
         assert_eq!(v, 1.0);
     }
 
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_amount_to_json_decimal() {
+        let a = Amount(n(1.0));
+        let v = json!(a);
+        assert_eq!(v, "1");
+    }
+
     #[test]
     fn test_json_to_amount() {
         let a: Amount = serde_json::from_str(r#"1.0"#).unwrap();
-        assert_eq!(a, Amount(1.0));
+        assert_eq!(a, Amount(n(1.0)));
+    }
+
+    #[test]
+    fn test_json_str_to_amount() {
+        let a: Amount = serde_json::from_str(r#""1.5""#).unwrap();
+        assert_eq!(a, Amount(n(1.5)));
+        let a: Amount = serde_json::from_str(r#"2"#).unwrap();
+        assert_eq!(a, Amount(n(2.0)));
+        assert!(serde_json::from_str::<Amount>(r#""x""#).is_err());
+    }
+
+    #[test]
+    fn test_json_to_negative_amount() {
+        let r: Result<Amount, _> = serde_json::from_str(r#"-1.0"#);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_amount_try_from_f64() {
+        assert_eq!(Amount::try_from(1.0).unwrap(), Amount(n(1.0)));
+        assert_eq!(Amount::try_from(-1.0), Err(NumError::Negative(n(-1.0))));
+        assert!(matches!(
+            Amount::try_from(f64::NAN),
+            Err(NumError::NotFinite(_))
+        ));
+        assert!(Amount::try_from(f64::INFINITY).is_err());
     }
 
     #[test]
     fn test_f64_div_amount() {
-        let a = Amount(10.);
-        let actual = 1. / a;
-        assert_eq!(Amount(0.1), actual)
+        let a = Amount(n(10.));
+        let actual = n(1.) / a;
+        assert_eq!(Amount(n(0.1)), actual)
     }
 }
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Currency, Exchange, Symbol, SymbolError, SymbolFormat};
+
+/// Registry of currency aliases: alias -> canonical currency.
+/// Global aliases apply to all exchanges, exchange ones override them.
+/// Only exchange aliases are used to map canonical currencies back to venue names.
+/// Currencies are uppercased on insert and on deserialize.
+///
+/// JSON: {"global":{"XBT":"BTC"},"exchanges":{"kraken":{"XXBT":"BTC","XBT":"BTC"}}}
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(from = "CurrencyAliasesRaw")]
+pub struct CurrencyAliases {
+    global: HashMap<Currency, Currency>,
+    exchanges: HashMap<Exchange, HashMap<Currency, Currency>>,
+}
+
+// CurrencyAliases as it comes on deserialize, not uppercased
+#[derive(Deserialize)]
+struct CurrencyAliasesRaw {
+    #[serde(default)]
+    global: HashMap<Currency, Currency>,
+    #[serde(default)]
+    exchanges: HashMap<Exchange, HashMap<Currency, Currency>>,
+}
+
+impl From<CurrencyAliasesRaw> for CurrencyAliases {
+    fn from(raw: CurrencyAliasesRaw) -> Self {
+        let mut aliases = Self::new();
+        for (alias, canonical) in raw.global {
+            aliases.insert_global(&alias, &canonical);
+        }
+        for (eg, m) in raw.exchanges {
+            for (alias, canonical) in m {
+                aliases.insert(&eg, &alias, &canonical);
+            }
+        }
+        aliases
+    }
+}
+
+impl CurrencyAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    /// Adds alias for all exchanges
+    pub fn insert_global(&mut self, alias: &str, canonical: &str) {
+        self.global
+            .insert(alias.to_uppercase(), canonical.to_uppercase());
+    }
+
+    /// Adds alias of the exchange
+    pub fn insert(&mut self, eg: &str, alias: &str, canonical: &str) {
+        self.exchanges
+            .entry(eg.to_string())
+            .or_default()
+            .insert(alias.to_uppercase(), canonical.to_uppercase());
+    }
+
+    /// Canonical currency of the exchange's currency (uppercased)
+    pub fn canonical(&self, eg: &str, c: &str) -> Currency {
+        let c = c.to_uppercase();
+        self.exchanges
+            .get(eg)
+            .and_then(|m| m.get(&c))
+            .or_else(|| self.global.get(&c))
+            .cloned()
+            .unwrap_or(c)
+    }
+
+    /// Venue-specific name of the canonical currency,
+    /// the first (sorted) alias if there are several of them
+    pub fn venue(&self, eg: &str, c: &str) -> Currency {
+        let c = c.to_uppercase();
+        self.exchanges
+            .get(eg)
+            .and_then(|m| {
+                m.iter()
+                    .filter(|(_, canonical)| **canonical == c)
+                    .map(|(alias, _)| alias)
+                    .min()
+            })
+            .cloned()
+            .unwrap_or(c)
+    }
+
+    /// Symbol with canonical currencies
+    pub fn symbol(&self, eg: Exchange, cb: &str, cq: &str) -> Symbol {
+        let (bs, qt) = (self.canonical(&eg, cb), self.canonical(&eg, cq));
+        Symbol::new(eg, bs, qt)
+    }
+
+    pub fn canonicalize(&self, symbol: &Symbol) -> Symbol {
+        self.symbol(symbol.eg.clone(), &symbol.bs, &symbol.qt)
+    }
+
+    /// Symbol with venue-specific currencies for outgoing requests
+    pub fn to_venue(&self, symbol: &Symbol) -> Symbol {
+        let bs = self.venue(&symbol.eg, &symbol.bs);
+        let qt = self.venue(&symbol.eg, &symbol.qt);
+        Symbol::new(symbol.eg.clone(), bs, qt)
+    }
+
+    /// Parses "eg:BASE/QUOTE" with canonical currencies
+    pub fn parse(&self, text: &str) -> Result<Symbol, SymbolError> {
+        Symbol::try_from(text).map(|s| self.canonicalize(&s))
+    }
+
+    /// Parses exchange-native symbol with canonical currencies
+    pub fn parse_with(
+        &self,
+        format: &impl SymbolFormat,
+        eg: Exchange,
+        text: &str,
+    ) -> Result<Symbol, SymbolError> {
+        format.parse(eg, text).map(|s| self.canonicalize(&s))
+    }
+
+    /// Renders exchange-native symbol with venue-specific currencies
+    pub fn render_with(&self, format: &impl SymbolFormat, symbol: &Symbol) -> String {
+        format.render(&self.to_venue(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VenueFormat;
+
+    fn aliases() -> CurrencyAliases {
+        CurrencyAliases::from_json(
+            r#"{"global":{"xbt":"btc"},"exchanges":{"kraken":{"XBT":"BTC","XXBT":"BTC"}}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_aliases_canonical() {
+        let a = aliases();
+        assert_eq!(a.canonical("eg", "xbt"), "BTC");
+        assert_eq!(a.canonical("eg", "eth"), "ETH");
+        let s = a.parse("eg:XBT/USDT").unwrap();
+        assert_eq!(s.to_string(), "eg:BTC/USDT");
+    }
+
+    #[test]
+    fn test_aliases_venue() {
+        let a = aliases();
+        let s = a
+            .parse_with(&VenueFormat::Kraken, "kraken".into(), "XXBTZUSD")
+            .unwrap();
+        assert_eq!(s.to_string(), "kraken:BTC/USD");
+        assert_eq!(a.render_with(&VenueFormat::KrakenWs, &s), "XBT/USD");
+        assert_eq!(a.venue("eg", "BTC"), "BTC");
+        assert_eq!(a.venue("kraken", "btc"), "XBT");
+    }
+
+    #[test]
+    fn test_aliases_uppercased() {
+        let a: CurrencyAliases =
+            serde_json::from_str(r#"{"exchanges":{"kraken":{"xxbt":"btc"}}}"#).unwrap();
+        assert_eq!(a.canonical("kraken", "XXBT"), "BTC");
+        let mut b = CurrencyAliases::new();
+        b.insert("kraken", "XxBt", "Btc");
+        assert_eq!(b, a);
+        b.insert_global("xbt", "btc");
+        assert_eq!(b.canonical("eg", "Xbt"), "BTC");
+    }
+}
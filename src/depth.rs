@@ -1,6 +1,9 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{DepthMap, Liq};
+use crate::{num_from_f64, Amount, DepthMap, Liq, LiqError, Price, Ratio, Side, NUM_ONE};
 
 /// Depth (order book)
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -9,6 +12,57 @@ pub struct Depth {
     pub bids: Vec<Liq>,
 }
 
+/// State of the top of the book
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossState {
+    Normal,
+    /// best bid == best ask
+    Locked,
+    /// best bid > best ask, usually after a missed update
+    Crossed,
+}
+
+impl CrossState {
+    pub fn from_best(best_bid: Option<Price>, best_ask: Option<Price>) -> Self {
+        match (best_bid, best_ask) {
+            (Some(b), Some(a)) if b > a => CrossState::Crossed,
+            (Some(b), Some(a)) if b == a => CrossState::Locked,
+            _ => CrossState::Normal,
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == CrossState::Normal
+    }
+}
+
+/// How to repair crossed or locked book: levels of one side that cross the best price
+/// of the other side are dropped
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossRepair {
+    /// The side of the latest update is right, the other side is stale
+    TrustLatest(Side),
+    /// The side updated earlier is stale. Depth doesn't track update times,
+    /// so the caller must supply the last update timestamps of the sides
+    /// (e.g. of the latest deltas with bids and with asks).
+    /// Nothing is dropped if timestamps are equal: the book needs resync
+    DropStale {
+        bids_ts: DateTime<Utc>,
+        asks_ts: DateTime<Utc>,
+    },
+}
+
+impl CrossRepair {
+    /// Sides to trim: (bids, asks), none if the stale side is unknown
+    pub fn stale_sides(&self) -> (bool, bool) {
+        match *self {
+            CrossRepair::TrustLatest(Side::Buy) => (false, true),
+            CrossRepair::TrustLatest(Side::Sell) => (true, false),
+            CrossRepair::DropStale { bids_ts, asks_ts } => (bids_ts < asks_ts, asks_ts < bids_ts),
+        }
+    }
+}
+
 impl Depth {
     pub fn new() -> Self {
         Self::default()
@@ -18,6 +72,165 @@ impl Depth {
         *self = other;
     }
 
+    /// Book side where orders of the side rest: bids for buy, asks for sell
+    pub fn side(&self, side: Side) -> &[Liq] {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    /// Book side which orders of the side take: asks for buy, bids for sell
+    pub fn opposite(&self, side: Side) -> &[Liq] {
+        self.side(side.invert())
+    }
+
+    /// Sets the level of the sorted book side (bids for buy, asks for sell),
+    /// zero amount removes it. Binary search, no re-sorting
+    pub fn update_level(&mut self, side: Side, liq: Liq) {
+        let (levels, pos) = match side {
+            Side::Buy => {
+                let pos = self.bids.binary_search_by(|l| liq.price().cmp(&l.price()));
+                (&mut self.bids, pos)
+            }
+            Side::Sell => {
+                let pos = self.asks.binary_search_by(|l| l.price().cmp(&liq.price()));
+                (&mut self.asks, pos)
+            }
+        };
+        match pos {
+            Ok(i) if liq.amount() == Amount::ZERO => {
+                levels.remove(i);
+            }
+            Ok(i) => levels[i] = liq,
+            Err(_) if liq.amount() == Amount::ZERO => {}
+            Err(i) => levels.insert(i, liq),
+        }
+    }
+
+    /// Applies depth change (delta) to the sorted depth, zero amounts remove levels
+    pub fn update(&mut self, depth_change: &Depth) {
+        for &l in &depth_change.asks {
+            self.update_level(Side::Sell, l);
+        }
+        for &l in &depth_change.bids {
+            self.update_level(Side::Buy, l);
+        }
+    }
+
+    pub fn cross_state(&self) -> CrossState {
+        let best_bid = self.bids.first().map(|l| l.price());
+        let best_ask = self.asks.first().map(|l| l.price());
+        CrossState::from_best(best_bid, best_ask)
+    }
+
+    /// Drops crossing (and locking) levels of the stale side,
+    /// returns the number of dropped levels and the state after repair
+    /// (still crossed or locked if the stale side is unknown)
+    pub fn repair_cross(&mut self, repair: CrossRepair) -> (usize, CrossState) {
+        let (Some(best_bid), Some(best_ask)) = (self.bids.first(), self.asks.first()) else {
+            return (0, self.cross_state());
+        };
+        let (best_bid, best_ask) = (best_bid.price(), best_ask.price());
+        let (trim_bids, trim_asks) = repair.stale_sides();
+        let len = self.bids.len() + self.asks.len();
+        if trim_bids {
+            self.bids.retain(|l| l.price() < best_ask);
+        }
+        if trim_asks {
+            self.asks.retain(|l| l.price() > best_bid);
+        }
+        (len - self.bids.len() - self.asks.len(), self.cross_state())
+    }
+
+    /// Replaces only the window covered by the partial (top-N) snapshot:
+    /// levels from the top to the worst snapshot level of each non-empty side.
+    /// Levels beyond the window are kept
+    pub fn update_partial(&mut self, snapshot: Depth) {
+        if let Some(worst) = snapshot.asks.iter().map(|l| l.price()).max() {
+            self.asks.retain(|l| l.price() > worst);
+            self.asks.splice(0..0, snapshot.asks);
+            self.asks.sort_by_key(|l| l.price());
+        }
+        if let Some(worst) = snapshot.bids.iter().map(|l| l.price()).min() {
+            self.bids.retain(|l| l.price() < worst);
+            self.bids.splice(0..0, snapshot.bids);
+            self.bids.sort_by_key(|l| Reverse(l.price()));
+        }
+        self.asks.retain(|l| l.amount() != Amount::ZERO);
+        self.bids.retain(|l| l.amount() != Amount::ZERO);
+    }
+
+    /// Keeps top n levels of each side
+    pub fn truncate(&mut self, n: usize) {
+        self.asks.truncate(n);
+        self.bids.truncate(n);
+    }
+
+    pub fn mid(&self) -> Option<Price> {
+        let best_bid = self.bids.first()?.price();
+        let best_ask = self.asks.first()?.price();
+        Some((best_bid + best_ask) / num_from_f64(2.0))
+    }
+
+    /// Keeps levels with prices within [lo, hi]
+    pub fn retain_band(&mut self, lo: Price, hi: Price) {
+        self.asks.retain(|l| l.price() >= lo && l.price() <= hi);
+        self.bids.retain(|l| l.price() >= lo && l.price() <= hi);
+    }
+
+    /// Keeps levels within the ratio (e.g. 1%) around mid, no-op for one-sided depth
+    pub fn retain_ratio_band(&mut self, r: Ratio) {
+        if let Some(mid) = self.mid() {
+            let (lo, hi) = ratio_band(mid, r);
+            self.retain_band(lo, hi);
+        }
+    }
+
+    /// Keeps levels within n ticks around mid, no-op for one-sided depth
+    pub fn retain_ticks_band(&mut self, ticks: usize, tick: Price) {
+        if let Some(mid) = self.mid() {
+            let (lo, hi) = ticks_band(mid, ticks, tick);
+            self.retain_band(lo, hi);
+        }
+    }
+
+    /// Depth of the inverse symbol (quote/base): bids become asks and vice versa,
+    /// see Liq::invert
+    pub fn invert(&self) -> Self {
+        let invert = |ll: &[Liq]| -> Vec<Liq> { ll.iter().filter_map(|l| l.invert()).collect() };
+        Depth {
+            asks: invert(&self.bids),
+            bids: invert(&self.asks),
+        }
+    }
+
+    /// Fallible constructor from exchange arrays of [price, amount] strings
+    pub fn try_from_strs<S, L>(aa: &[L], bb: &[L]) -> Result<Self, LiqError>
+    where
+        S: AsRef<str>,
+        L: AsRef<[S]>,
+    {
+        let liqs = |ll: &[L]| -> Result<Vec<Liq>, LiqError> {
+            ll.iter().map(|l| Liq::try_from_strs(l.as_ref())).collect()
+        };
+        Ok(Depth {
+            asks: liqs(aa)?,
+            bids: liqs(bb)?,
+        })
+    }
+
+    /// Fallible constructor from exchange arrays of [price, amount] numbers
+    pub fn try_from_f64s<L: AsRef<[f64]>>(aa: &[L], bb: &[L]) -> Result<Self, LiqError> {
+        let liqs = |ll: &[L]| -> Result<Vec<Liq>, LiqError> {
+            ll.iter().map(|l| Liq::try_from_f64s(l.as_ref())).collect()
+        };
+        Ok(Depth {
+            asks: liqs(aa)?,
+            bids: liqs(bb)?,
+        })
+    }
+
     pub fn into_depthmap(self) -> DepthMap {
         let mut m = DepthMap::new();
         m.asks = self.asks.into_iter().map(|a| (a.p, a)).collect();
@@ -26,6 +239,16 @@ impl Depth {
     }
 }
 
+// lower bounds are clamped at zero for wide bands
+pub(crate) fn ratio_band(mid: Price, r: Ratio) -> (Price, Price) {
+    ((mid * (NUM_ONE - r)).max(Price::ZERO), mid * (NUM_ONE + r))
+}
+
+pub(crate) fn ticks_band(mid: Price, ticks: usize, tick: Price) -> (Price, Price) {
+    let delta = tick * num_from_f64(ticks as f64);
+    ((mid - delta).max(Price::ZERO), mid + delta)
+}
+
 impl From<(&[&[String]], &[&[String]])> for Depth {
     fn from((aa, bb): (&[&[String]], &[&[String]])) -> Self {
         let aa: Vec<Liq> = aa.iter().map(|&a| a.into()).collect();
@@ -49,3 +272,117 @@ impl From<(&[&[f64]], &[&[f64]])> for Depth {
         Depth { asks: aa, bids: bb }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{liq, n, Price};
+
+    #[test]
+    fn test_depth_try_from_strs() {
+        let aa = vec![vec!["11".to_string(), "1".to_string()]];
+        let bb = vec![vec!["10".to_string(), "2".to_string()]];
+        let d = Depth::try_from_strs(&aa, &bb).unwrap();
+        assert_eq!(d.bids, vec![Liq::from_pa(Price(n(10.0)), Amount(n(2.0)))]);
+        let bb = vec![vec!["10".to_string()]];
+        assert_eq!(
+            Depth::try_from_strs(&aa, &bb).unwrap_err(),
+            LiqError::TooShort(1)
+        );
+        let r = Depth::try_from_f64s(&[[11.0, 1.0]], &[[f64::NAN, 1.0]]);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_depth_repair_cross() {
+        let crossed = Depth {
+            asks: vec![liq(10.0, 1.0), liq(10.5, 1.0), liq(11.0, 1.0)],
+            bids: vec![liq(10.6, 1.0), liq(10.0, 1.0), liq(9.0, 1.0)],
+        };
+        assert_eq!(crossed.cross_state(), CrossState::Crossed);
+        let mut d = crossed.clone();
+        assert_eq!(
+            d.repair_cross(CrossRepair::TrustLatest(Side::Buy)),
+            (2, CrossState::Normal)
+        );
+        assert_eq!(d.asks, vec![liq(11.0, 1.0)]);
+        let mut d = crossed.clone();
+        let (t1, t2) = (Utc::now(), Utc::now() + chrono::Duration::seconds(1));
+        let repair = CrossRepair::DropStale {
+            bids_ts: t1,
+            asks_ts: t2,
+        };
+        assert_eq!(d.repair_cross(repair), (2, CrossState::Normal));
+        assert_eq!(d.bids, vec![liq(9.0, 1.0)]);
+        let mut d = crossed.clone();
+        let repair = CrossRepair::DropStale {
+            bids_ts: t1,
+            asks_ts: t1,
+        };
+        assert_eq!(d.repair_cross(repair), (0, CrossState::Crossed));
+        assert_eq!(d.bids.len(), 3);
+        let locked = Depth {
+            asks: vec![liq(10.0, 1.0)],
+            bids: vec![liq(10.0, 1.0)],
+        };
+        assert_eq!(locked.cross_state(), CrossState::Locked);
+    }
+
+    #[test]
+    fn test_depth_partial_and_band() {
+        let mut d = Depth {
+            asks: vec![
+                liq(11.0, 1.0),
+                liq(12.0, 1.0),
+                liq(13.0, 1.0),
+                liq(14.0, 1.0),
+            ],
+            bids: vec![liq(10.0, 1.0), liq(9.0, 1.0), liq(8.0, 1.0)],
+        };
+        d.update_partial(Depth {
+            asks: vec![liq(11.5, 2.0), liq(12.0, 3.0)],
+            bids: vec![],
+        });
+        assert_eq!(
+            d.asks,
+            vec![
+                liq(11.5, 2.0),
+                liq(12.0, 3.0),
+                liq(13.0, 1.0),
+                liq(14.0, 1.0)
+            ]
+        );
+        assert_eq!(d.bids.len(), 3);
+        assert_eq!(d.mid(), Some(Price(n(10.75))));
+        let mut d2 = d.clone();
+        d2.retain_ticks_band(2, Price(n(1.0)));
+        assert_eq!(d2.asks, vec![liq(11.5, 2.0), liq(12.0, 3.0)]);
+        assert_eq!(d2.bids, vec![liq(10.0, 1.0), liq(9.0, 1.0)]);
+        d.retain_ratio_band(Ratio::from_percent(n(20.0)));
+        assert_eq!(d.bids.len(), 2);
+        assert_eq!(
+            ticks_band(Price(n(1.0)), 3, Price(n(1.0))),
+            (Price::ZERO, Price(n(4.0)))
+        );
+        assert_eq!(
+            ratio_band(Price(n(1.0)), Ratio::from_percent(n(150.0))).0,
+            Price::ZERO
+        );
+        d.truncate(1);
+        assert_eq!(d.asks, vec![liq(11.5, 2.0)]);
+    }
+
+    #[test]
+    fn test_depth_update() {
+        let mut d = Depth {
+            asks: vec![liq(11.0, 1.0), liq(12.0, 1.0)],
+            bids: vec![liq(10.0, 1.0), liq(9.0, 1.0)],
+        };
+        d.update(&Depth {
+            asks: vec![liq(11.0, 0.0), liq(11.5, 2.0), liq(14.0, 0.0)],
+            bids: vec![liq(9.5, 1.0), liq(10.0, 3.0)],
+        });
+        assert_eq!(d.asks, vec![liq(11.5, 2.0), liq(12.0, 1.0)]);
+        assert_eq!(d.bids, vec![liq(10.0, 3.0), liq(9.5, 1.0), liq(9.0, 1.0)]);
+    }
+}
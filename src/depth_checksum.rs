@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    num_to_string_prec, Amount, Depth, DepthMap, InstrumentSpec, Liq, Num, Price, SortedDepth,
+};
+
+/// Venue algorithm of CRC32 checksum over the top levels of the depth
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumKind {
+    /// "bidP:bidA:askP:askA:..." interleaved, top 25
+    #[serde(rename = "okx", alias = "OKX", alias = "Okx")]
+    Okx,
+    /// asks then bids, "pricequantity" without dots and leading zeros, top 10
+    #[serde(rename = "kraken", alias = "Kraken")]
+    Kraken,
+    /// "bidP:bidA:askP:-askA:..." interleaved, top 25
+    #[serde(rename = "bitfinex", alias = "Bitfinex")]
+    Bitfinex,
+}
+
+impl ChecksumKind {
+    pub fn levels(&self) -> usize {
+        match self {
+            ChecksumKind::Okx | ChecksumKind::Bitfinex => 25,
+            ChecksumKind::Kraken => 10,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ChecksumError {
+    #[error("depth checksum mismatch: expected {expected}, computed {computed}")]
+    Mismatch { expected: u32, computed: u32 },
+    #[error("expected checksum {0} is out of i32 and u32 range")]
+    BadExpected(i64),
+}
+
+/// Depth checksum calculator. Strings must match the venue formatting exactly,
+/// so set precisions of the instrument (required by Kraken);
+/// without them the shortest number representation is used
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DepthChecksum {
+    pub kind: ChecksumKind,
+    pub levels: usize,
+    pub price_precision: Option<usize>,
+    pub amount_precision: Option<usize>,
+}
+
+impl DepthChecksum {
+    pub fn new(kind: ChecksumKind) -> Self {
+        Self {
+            kind,
+            levels: kind.levels(),
+            price_precision: None,
+            amount_precision: None,
+        }
+    }
+
+    pub fn for_spec(kind: ChecksumKind, spec: &InstrumentSpec) -> Self {
+        Self {
+            price_precision: Some(spec.price_precision()),
+            amount_precision: Some(spec.amount_precision()),
+            ..Self::new(kind)
+        }
+    }
+
+    /// CRC32 of the sorted depth, signed venues (OKX, Bitfinex) send it as i32
+    pub fn compute(&self, depth: &Depth) -> u32 {
+        self.compute_levels(&depth.asks, &depth.bids)
+    }
+
+    pub fn compute_map(&self, depth: &DepthMap) -> u32 {
+        self.compute(&depth.clone().into_depth())
+    }
+
+    pub fn compute_sorted(&self, depth: &SortedDepth) -> u32 {
+        let asks: Vec<Liq> = depth.asks().take(self.levels).copied().collect();
+        let bids: Vec<Liq> = depth.bids().take(self.levels).copied().collect();
+        self.compute_levels(&asks, &bids)
+    }
+
+    /// Verifies the venue checksum (signed or unsigned), mismatch means the depth needs resync
+    pub fn verify(&self, depth: &Depth, expected: i64) -> Result<(), ChecksumError> {
+        check(self.compute(depth), expected)
+    }
+
+    pub fn verify_map(&self, depth: &DepthMap, expected: i64) -> Result<(), ChecksumError> {
+        check(self.compute_map(depth), expected)
+    }
+
+    pub fn verify_sorted(&self, depth: &SortedDepth, expected: i64) -> Result<(), ChecksumError> {
+        check(self.compute_sorted(depth), expected)
+    }
+
+    // private helpers
+
+    fn compute_levels(&self, asks: &[Liq], bids: &[Liq]) -> u32 {
+        crc32fast::hash(self.payload(asks, bids).as_bytes())
+    }
+
+    fn payload(&self, asks: &[Liq], bids: &[Liq]) -> String {
+        let asks = &asks[..asks.len().min(self.levels)];
+        let bids = &bids[..bids.len().min(self.levels)];
+        let mut parts: Vec<String> = Vec::with_capacity(4 * self.levels);
+        match self.kind {
+            ChecksumKind::Okx | ChecksumKind::Bitfinex => {
+                let ask_sign = if self.kind == ChecksumKind::Bitfinex {
+                    "-"
+                } else {
+                    ""
+                };
+                for i in 0..self.levels {
+                    if let Some(b) = bids.get(i) {
+                        parts.push(self.price(b.price()));
+                        parts.push(self.amount(b.amount()));
+                    }
+                    if let Some(a) = asks.get(i) {
+                        parts.push(self.price(a.price()));
+                        parts.push(format!("{}{}", ask_sign, self.amount(a.amount())));
+                    }
+                }
+                parts.join(":")
+            }
+            ChecksumKind::Kraken => {
+                let kraken = |s: String| s.replace('.', "").trim_start_matches('0').to_string();
+                for l in asks.iter().chain(bids) {
+                    parts.push(kraken(self.price(l.price())));
+                    parts.push(kraken(self.amount(l.amount())));
+                }
+                parts.concat()
+            }
+        }
+    }
+
+    fn price(&self, p: Price) -> String {
+        to_string(p.0, self.price_precision)
+    }
+
+    fn amount(&self, a: Amount) -> String {
+        to_string(a.0, self.amount_precision)
+    }
+}
+
+fn to_string(v: Num, precision: Option<usize>) -> String {
+    match precision {
+        Some(prec) => num_to_string_prec(v, prec),
+        None => v.to_string(),
+    }
+}
+
+fn check(computed: u32, expected: i64) -> Result<(), ChecksumError> {
+    // signed i32 and unsigned u32 checksums have the same bits
+    let expected = match (i32::try_from(expected), u32::try_from(expected)) {
+        (_, Ok(v)) => v,
+        (Ok(v), _) => v as u32,
+        _ => return Err(ChecksumError::BadExpected(expected)),
+    };
+    if computed != expected {
+        return Err(ChecksumError::Mismatch { expected, computed });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::liq;
+
+    fn depth() -> Depth {
+        Depth {
+            asks: vec![liq(3366.8, 9.0), liq(3368.0, 8.0), liq(3372.0, 8.0)],
+            bids: vec![liq(3366.1, 7.0), liq(3366.0, 6.0)],
+        }
+    }
+
+    #[test]
+    fn test_checksum_payload() {
+        let d = depth();
+        let okx = DepthChecksum::new(ChecksumKind::Okx);
+        assert_eq!(
+            okx.payload(&d.asks, &d.bids),
+            "3366.1:7:3366.8:9:3366:6:3368:8:3372:8"
+        );
+        let bfx = DepthChecksum::new(ChecksumKind::Bitfinex);
+        assert_eq!(
+            bfx.payload(&d.asks, &d.bids),
+            "3366.1:7:3366.8:-9:3366:6:3368:-8:3372:-8"
+        );
+        let mut kraken = DepthChecksum::new(ChecksumKind::Kraken);
+        kraken.price_precision = Some(1);
+        kraken.amount_precision = Some(3);
+        kraken.levels = 2;
+        assert_eq!(
+            kraken.payload(&d.asks, &d.bids),
+            "336689000336808000336617000336606000"
+        );
+    }
+
+    #[test]
+    fn test_checksum_verify() {
+        let d = depth();
+        let okx = DepthChecksum::new(ChecksumKind::Okx);
+        let crc = okx.compute(&d);
+        assert!(okx.verify(&d, crc as i32 as i64).is_ok());
+        assert!(okx
+            .verify_map(&d.clone().into_depthmap(), crc as i64)
+            .is_ok());
+        assert!(okx
+            .verify_sorted(&SortedDepth::from(d.clone()), crc as i64)
+            .is_ok());
+        assert_eq!(
+            okx.verify(&d, 1),
+            Err(ChecksumError::Mismatch {
+                expected: 1,
+                computed: crc
+            })
+        );
+        assert_eq!(
+            okx.verify(&d, 1 << 32),
+            Err(ChecksumError::BadExpected(1 << 32))
+        );
+        assert_eq!(
+            okx.verify(&d, i32::MIN as i64 - 1),
+            Err(ChecksumError::BadExpected(i32::MIN as i64 - 1))
+        );
+    }
+
+    #[test]
+    fn test_checksum_okx_docs() {
+        // example of OKX API docs: bids 3366.1/7, 3366/6, asks 3366.8/9, 3368/8
+        let d = Depth {
+            asks: vec![liq(3366.8, 9.0), liq(3368.0, 8.0)],
+            bids: vec![liq(3366.1, 7.0), liq(3366.0, 6.0)],
+        };
+        let okx = DepthChecksum::new(ChecksumKind::Okx);
+        assert!(okx.verify(&d, -1881014294).is_ok());
+    }
+
+    #[test]
+    fn test_checksum_kraken_docs() {
+        // example book of Kraken websocket docs (book checksum guide)
+        let d = Depth {
+            asks: [
+                0.05005, 0.0501, 0.05015, 0.0502, 0.05025, 0.0503, 0.05035, 0.0504, 0.05045, 0.0505,
+            ]
+            .into_iter()
+            .map(|p| liq(p, 0.000005))
+            .collect(),
+            bids: [
+                0.05, 0.04995, 0.0499, 0.0498, 0.04975, 0.0497, 0.04965, 0.0496, 0.04955, 0.0495,
+            ]
+            .into_iter()
+            .map(|p| liq(p, 0.000005))
+            .collect(),
+        };
+        let mut kraken = DepthChecksum::new(ChecksumKind::Kraken);
+        kraken.price_precision = Some(5);
+        kraken.amount_precision = Some(8);
+        assert!(kraken.verify(&d, 974947235).is_ok());
+    }
+
+    #[test]
+    fn test_checksum_bitfinex() {
+        // crc32 of "8000:0.5:8000.5:-0.75:7999.5:1.25:8001:-2", signed as Bitfinex sends it
+        let d = Depth {
+            asks: vec![liq(8000.5, 0.75), liq(8001.0, 2.0)],
+            bids: vec![liq(8000.0, 0.5), liq(7999.5, 1.25)],
+        };
+        let bfx = DepthChecksum::new(ChecksumKind::Bitfinex);
+        assert!(bfx.verify(&d, -605047322).is_ok());
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Depth, SortedDepth};
+
+/// Depth snapshot with the id of the last update included in it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DepthSnapshot {
+    pub last_id: u64,
+    pub depth: Depth,
+}
+
+/// Depth change (delta) of updates from first_id to last_id.
+/// prev_id is the last id of the previous delta if the venue sends it (Binance futures "pu"),
+/// otherwise deltas must be contiguous by ids
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DepthDelta {
+    pub first_id: u64,
+    pub last_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_id: Option<u64>,
+    pub depth: Depth,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncState {
+    WaitingSnapshot,
+    Synced,
+    NeedsResync,
+}
+
+/// What happened to the delta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaOutcome {
+    Applied,
+    Buffered,
+    Stale,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum DepthSyncError {
+    #[error("gap in depth updates: expected {expected}, got {first_id}..{last_id}")]
+    Gap {
+        expected: u64,
+        first_id: u64,
+        last_id: u64,
+    },
+}
+
+/// Book sync state machine of snapshot-plus-diff protocols (Binance/Bybit style):
+/// deltas are buffered until a snapshot arrives, stale deltas are dropped,
+/// a gap switches the state to NeedsResync (a new snapshot is required)
+#[derive(Debug, Clone)]
+pub struct DepthSync {
+    depth: SortedDepth,
+    state: SyncState,
+    last_id: Option<u64>,
+    // the first delta after a snapshot may overlap it
+    after_snapshot: bool,
+    buffer: VecDeque<DepthDelta>,
+    // the oldest deltas are dropped above it, it results in a gap on the snapshot
+    pub max_buffer: usize,
+}
+
+impl Default for DepthSync {
+    fn default() -> Self {
+        Self {
+            depth: SortedDepth::new(),
+            state: SyncState::WaitingSnapshot,
+            last_id: None,
+            after_snapshot: false,
+            buffer: VecDeque::new(),
+            max_buffer: 10_000,
+        }
+    }
+}
+
+impl DepthSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> SyncState {
+        self.state
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.state == SyncState::Synced
+    }
+
+    /// Id of the last applied update
+    pub fn last_id(&self) -> Option<u64> {
+        self.last_id
+    }
+
+    pub fn depth(&self) -> &SortedDepth {
+        &self.depth
+    }
+
+    /// Drops the book, deltas are buffered until the next snapshot
+    pub fn reset(&mut self) {
+        self.depth = SortedDepth::new();
+        self.state = SyncState::WaitingSnapshot;
+        self.last_id = None;
+        self.after_snapshot = false;
+    }
+
+    /// Replaces the book and applies buffered deltas newer than the snapshot
+    pub fn apply_snapshot(&mut self, snapshot: DepthSnapshot) -> Result<SyncState, DepthSyncError> {
+        self.depth.replace(snapshot.depth);
+        self.last_id = Some(snapshot.last_id);
+        self.after_snapshot = true;
+        self.state = SyncState::Synced;
+        // on a gap the rest deltas are buffered again for the next snapshot
+        let mut gap = None;
+        for delta in std::mem::take(&mut self.buffer) {
+            if let Err(e) = self.apply_delta(delta) {
+                gap.get_or_insert(e);
+            }
+        }
+        match gap {
+            Some(e) => Err(e),
+            None => Ok(self.state),
+        }
+    }
+
+    /// Applies the delta if synced, buffers it otherwise
+    pub fn apply_delta(&mut self, delta: DepthDelta) -> Result<DeltaOutcome, DepthSyncError> {
+        let Some(last_id) = self.last_id.filter(|_| self.is_synced()) else {
+            self.buffer_delta(delta);
+            return Ok(DeltaOutcome::Buffered);
+        };
+        if delta.last_id <= last_id {
+            return Ok(DeltaOutcome::Stale);
+        }
+        let contiguous = match delta.prev_id {
+            Some(prev_id) if !self.after_snapshot => prev_id == last_id,
+            _ if self.after_snapshot => delta.first_id <= last_id + 1,
+            _ => delta.first_id == last_id + 1,
+        };
+        if !contiguous {
+            let e = DepthSyncError::Gap {
+                expected: last_id + 1,
+                first_id: delta.first_id,
+                last_id: delta.last_id,
+            };
+            self.state = SyncState::NeedsResync;
+            self.buffer_delta(delta);
+            return Err(e);
+        }
+        self.depth.update(&delta.depth);
+        self.last_id = Some(delta.last_id);
+        self.after_snapshot = false;
+        Ok(DeltaOutcome::Applied)
+    }
+
+    // private helpers
+
+    fn buffer_delta(&mut self, delta: DepthDelta) {
+        if self.buffer.len() >= self.max_buffer {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{n, Amount, Liq, Price};
+
+    fn delta(first_id: u64, last_id: u64, bid: f64) -> DepthDelta {
+        DepthDelta {
+            first_id,
+            last_id,
+            prev_id: None,
+            depth: Depth {
+                asks: vec![],
+                bids: vec![Liq::from_pa(Price(n(bid)), Amount(n(1.0)))],
+            },
+        }
+    }
+
+    fn snapshot(last_id: u64) -> DepthSnapshot {
+        DepthSnapshot {
+            last_id,
+            depth: Depth::new(),
+        }
+    }
+
+    #[test]
+    fn test_sync_buffer_then_snapshot() {
+        let mut s = DepthSync::new();
+        assert_eq!(s.apply_delta(delta(1, 5, 1.0)), Ok(DeltaOutcome::Buffered));
+        assert_eq!(s.apply_delta(delta(6, 8, 2.0)), Ok(DeltaOutcome::Buffered));
+        assert_eq!(s.apply_delta(delta(9, 9, 3.0)), Ok(DeltaOutcome::Buffered));
+        assert_eq!(s.apply_snapshot(snapshot(7)), Ok(SyncState::Synced));
+        assert_eq!(s.last_id(), Some(9));
+        assert_eq!(s.depth().bids().len(), 2);
+        assert_eq!(s.apply_delta(delta(8, 9, 4.0)), Ok(DeltaOutcome::Stale));
+        assert_eq!(s.apply_delta(delta(10, 10, 4.0)), Ok(DeltaOutcome::Applied));
+    }
+
+    #[test]
+    fn test_sync_gap() {
+        let mut s = DepthSync::new();
+        s.apply_snapshot(snapshot(10)).unwrap();
+        s.apply_delta(delta(11, 12, 1.0)).unwrap();
+        assert_eq!(
+            s.apply_delta(delta(14, 15, 2.0)),
+            Err(DepthSyncError::Gap {
+                expected: 13,
+                first_id: 14,
+                last_id: 15
+            })
+        );
+        assert_eq!(s.state(), SyncState::NeedsResync);
+        assert_eq!(
+            s.apply_delta(delta(16, 16, 3.0)),
+            Ok(DeltaOutcome::Buffered)
+        );
+        assert_eq!(s.apply_snapshot(snapshot(14)), Ok(SyncState::Synced));
+        assert_eq!(s.last_id(), Some(16));
+    }
+
+    #[test]
+    fn test_sync_snapshot_too_old() {
+        let mut s = DepthSync::new();
+        s.apply_delta(delta(20, 21, 1.0)).unwrap();
+        assert!(s.apply_snapshot(snapshot(10)).is_err());
+        assert_eq!(s.state(), SyncState::NeedsResync);
+    }
+
+    #[test]
+    fn test_sync_prev_id() {
+        let mut s = DepthSync::new();
+        s.apply_snapshot(snapshot(10)).unwrap();
+        let mut d = delta(5, 12, 1.0);
+        d.prev_id = Some(4);
+        assert_eq!(s.apply_delta(d), Ok(DeltaOutcome::Applied));
+        let mut d = delta(20, 25, 1.0);
+        d.prev_id = Some(12);
+        assert_eq!(s.apply_delta(d), Ok(DeltaOutcome::Applied));
+        let mut d = delta(26, 27, 1.0);
+        d.prev_id = Some(24);
+        assert!(s.apply_delta(d).is_err());
+    }
+}
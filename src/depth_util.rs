@@ -1,6 +1,8 @@
-use super::{Amount, Liq, Price, Worth};
+use std::cmp::Ordering;
 
-/// Merges liqs(orders) by price (L2)
+use super::{Amount, Depth, Liq, Price, Ratio, Side, Symbol, SymbolError, Worth};
+
+/// Merges liqs(orders) by price (L2): adjacent liqs of the same price, the order is kept
 pub fn liqs_l2(liqs: &[Liq]) -> Vec<Liq> {
     let mut l2: Vec<Liq> = Vec::with_capacity(liqs.len());
     let mut prev_opt: Option<Liq> = None;
@@ -23,6 +25,29 @@ pub fn liqs_l2(liqs: &[Liq]) -> Vec<Liq> {
     l2
 }
 
+/// Merges liqs(orders) of the book side (bids for buy, asks for sell) by price (L2),
+/// levels are sorted from the best one
+pub fn liqs_l2_side(liqs: &[Liq], side: Side) -> Vec<Liq> {
+    let mut l2: Vec<Liq> = Vec::with_capacity(liqs.len());
+    for &l in liqs {
+        // sorted input is appended to the end
+        let pos = l2.binary_search_by(|x| {
+            if side.is_better(x.price(), l.price()) {
+                Ordering::Less
+            } else if side.is_better(l.price(), x.price()) {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        });
+        match pos {
+            Ok(i) => l2[i] = Liq::from((l.price(), l2[i].amount() + l.amount())),
+            Err(i) => l2.insert(i, l),
+        }
+    }
+    l2
+}
+
 /// Drops target worth from the depth, usually cleaining it from noise
 pub fn drop_worth(ll: &[Liq], ll_must_drop: &[Liq], drop_worth: Worth) -> Vec<Liq> {
     // unique prices
@@ -36,7 +61,7 @@ pub fn drop_worth(ll: &[Liq], ll_must_drop: &[Liq], drop_worth: Worth) -> Vec<Li
                     l = Liq::from((l.price(), l.amount() - l_to_drop.amount()));
                 }
             }
-            if l.amount() > Amount(0.0) {
+            if l.amount() > Amount::ZERO {
                 Some(l)
             } else {
                 None
@@ -89,90 +114,273 @@ pub fn worst_execution_price(ll: &[Liq], w: Worth) -> Option<Price> {
         .map(|x| x.p)
 }
 
+/// Calcs worst execution price of the taker order of the side
+/// (buy takes asks, sell takes bids)
+pub fn worst_execution_price_side(depth: &Depth, side: Side, w: Worth) -> Option<Price> {
+    worst_execution_price(depth.opposite(side), w)
+}
+
+/// Calcs slippage of the execution of the worth as a ratio
+/// between the best (first) price and the worst execution price
+pub fn slippage(ll: &[Liq], w: Worth) -> Option<Ratio> {
+    let best = ll.first()?.p;
+    let worst = worst_execution_price(ll, w)?;
+    // execution moves away from the best price: down the bids or up the asks
+    let side = if Side::Buy.is_better(best, worst) {
+        Side::Buy
+    } else {
+        Side::Sell
+    };
+    book_slippage(ll, side, w)
+}
+
+/// Calcs slippage of the taker order of the side
+pub fn slippage_side(depth: &Depth, side: Side, w: Worth) -> Option<Ratio> {
+    book_slippage(depth.opposite(side), side.invert(), w)
+}
+
+// Slippage over the levels of the book side: the worst price is never better than the best one
+fn book_slippage(ll: &[Liq], side: Side, w: Worth) -> Option<Ratio> {
+    let best = ll.first()?.p;
+    let worst = worst_execution_price(ll, w)?;
+    let delta = (best - worst) * side.sign();
+    Some(Ratio(delta / best))
+}
+
+/// Synthetic cross depth of two legs sharing a currency,
+/// e.g. ETH/BTC of ETH/USDT and BTC/USDT (legs are inverted as needed).
+/// Levels consume liquidity of both legs, so every level is executable
+pub fn synthetic_cross(
+    s1: &Symbol,
+    d1: &Depth,
+    s2: &Symbol,
+    d2: &Depth,
+) -> Result<(Symbol, Depth), SymbolError> {
+    // normalize to A/X and X/B
+    let (s1, d1, s2, d2) = if s1.qt == s2.bs {
+        (s1.clone(), d1.clone(), s2.clone(), d2.clone())
+    } else if s1.qt == s2.qt {
+        (s1.clone(), d1.clone(), s2.invert(), d2.invert())
+    } else if s1.bs == s2.bs {
+        (s1.invert(), d1.invert(), s2.clone(), d2.clone())
+    } else if s1.bs == s2.qt {
+        (s1.invert(), d1.invert(), s2.invert(), d2.invert())
+    } else {
+        return Err(SymbolError::NoCommonCurrency(
+            s1.to_string(),
+            s2.to_string(),
+        ));
+    };
+    let eg = if s1.eg == s2.eg {
+        s1.eg.clone()
+    } else {
+        format!("{}+{}", s1.eg, s2.eg)
+    };
+    let symbol = Symbol::new(eg, s1.bs.clone(), s2.qt.clone());
+    let depth = Depth {
+        asks: cross_liqs(&d1.asks, &d2.asks, Side::Sell),
+        bids: cross_liqs(&d1.bids, &d2.bids, Side::Buy),
+    };
+    Ok((symbol, depth))
+}
+
+// Composes A/X and X/B levels of the same side into A/B levels:
+// price p1 * p2, amount is limited by both legs
+fn cross_liqs(ll1: &[Liq], ll2: &[Liq], side: Side) -> Vec<Liq> {
+    let mut cross: Vec<Liq> = Vec::with_capacity(ll1.len() + ll2.len());
+    let (mut i, mut j) = (0, 0);
+    let mut a1_remains = ll1.first().map_or(Amount::ZERO, |l| l.amount()); // in A
+    let mut a2_remains = ll2.first().map_or(Amount::ZERO, |l| l.amount()); // in X
+    while i < ll1.len() && j < ll2.len() {
+        let (p1, p2) = (ll1[i].price(), ll2[j].price());
+        let a2_in_a = Amount(a2_remains.0 / p1.0);
+        let a = if a1_remains <= a2_in_a {
+            a2_remains -= Amount(a1_remains.0 * p1.0);
+            i += 1;
+            let a = a1_remains;
+            a1_remains = ll1.get(i).map_or(Amount::ZERO, |l| l.amount());
+            a
+        } else {
+            a1_remains -= a2_in_a;
+            j += 1;
+            a2_remains = ll2.get(j).map_or(Amount::ZERO, |l| l.amount());
+            a2_in_a
+        };
+        if a > Amount::ZERO {
+            cross.push(Liq::from_pa(Price(p1.0 * p2.0), a));
+        }
+    }
+    liqs_l2_side(&cross, side)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{liq, n};
 
     fn ll() -> Vec<Liq> {
         vec![
-            Liq::from((Price(1.0), Amount(10.0))), // W(10)
-            Liq::from((Price(2.0), Amount(10.0))), // W(20)
+            Liq::from((Price(n(1.0)), Amount(n(10.0)))), // W(10)
+            Liq::from((Price(n(2.0)), Amount(n(10.0)))), // W(20)
         ]
     }
 
     #[test]
     pub fn test_worst_execution_price_0() {
-        let w = Worth(5.0);
-        assert_eq!(worst_execution_price(&ll(), w), Some(Price(1.0)));
+        let w = Worth(n(5.0));
+        assert_eq!(worst_execution_price(&ll(), w), Some(Price(n(1.0))));
     }
 
     #[test]
     pub fn test_worst_execution_price_1() {
-        let w = Worth(10.0);
-        assert_eq!(worst_execution_price(&ll(), w), Some(Price(1.0)));
+        let w = Worth(n(10.0));
+        assert_eq!(worst_execution_price(&ll(), w), Some(Price(n(1.0))));
     }
 
     #[test]
     pub fn test_worst_execution_price_2() {
-        let w = Worth(20.0);
-        assert_eq!(worst_execution_price(&ll(), w), Some(Price(2.0)));
+        let w = Worth(n(20.0));
+        assert_eq!(worst_execution_price(&ll(), w), Some(Price(n(2.0))));
     }
 
     #[test]
     pub fn test_worst_execution_price_3() {
-        let w = Worth(30.0);
-        assert_eq!(worst_execution_price(&ll(), w), Some(Price(2.0)));
+        let w = Worth(n(30.0));
+        assert_eq!(worst_execution_price(&ll(), w), Some(Price(n(2.0))));
     }
 
     #[test]
     pub fn test_worst_execution_price_4() {
-        let w = Worth(50.0);
+        let w = Worth(n(50.0));
         assert_eq!(worst_execution_price(&ll(), w), None);
     }
 
+    #[test]
+    pub fn test_slippage() {
+        assert_eq!(slippage(&ll(), Worth(n(5.0))), Some(Ratio::ZERO));
+        assert_eq!(slippage(&ll(), Worth(n(20.0))), Some(Ratio::ONE));
+        assert_eq!(slippage(&ll(), Worth(n(50.0))), None);
+    }
+
+    #[test]
+    pub fn test_execution_side() {
+        let d = Depth {
+            asks: ll(),
+            bids: vec![Liq::from_pa(Price(n(0.5)), Amount(n(10.0)))],
+        };
+        assert_eq!(
+            worst_execution_price_side(&d, Side::Buy, Worth(n(20.0))),
+            Some(Price(n(2.0)))
+        );
+        assert_eq!(
+            worst_execution_price_side(&d, Side::Sell, Worth(n(1.0))),
+            Some(Price(n(0.5)))
+        );
+        assert_eq!(
+            slippage_side(&d, Side::Sell, Worth(n(1.0))),
+            Some(Ratio::ZERO)
+        );
+        assert_eq!(d.side(Side::Sell)[0].price(), Price(n(1.0)));
+    }
+
+    #[test]
+    fn test_synthetic_cross() {
+        let eth_usdt = Symbol::try_from("eg:ETH/USDT").unwrap();
+        let btc_usdt = Symbol::try_from("eg:BTC/USDT").unwrap();
+        let d1 = Depth {
+            asks: vec![
+                Liq::from_pa(Price(n(2000.0)), Amount(n(1.0))),
+                Liq::from_pa(Price(n(2100.0)), Amount(n(1.0))),
+            ],
+            bids: vec![Liq::from_pa(Price(n(1900.0)), Amount(n(1.0)))],
+        };
+        let d2 = Depth {
+            asks: vec![Liq::from_pa(Price(n(40000.0)), Amount(n(1.0)))],
+            bids: vec![
+                Liq::from_pa(Price(n(32000.0)), Amount(n(0.025))), // 800 USDT
+                Liq::from_pa(Price(n(30000.0)), Amount(n(1.0))),
+            ],
+        };
+        let (s, d) = synthetic_cross(&eth_usdt, &d1, &btc_usdt, &d2).unwrap();
+        assert_eq!(s.to_string(), "eg:ETH/BTC");
+        // buy ETH for BTC: sell BTC at its bids, buy ETH at its asks
+        assert_eq!(d.asks[0].price(), Price(n(2000.0) * (n(1.0) / n(32000.0))));
+        assert_eq!(d.asks[0].amount(), Amount(n(0.4)));
+        assert_eq!(d.asks[1].price(), Price(n(2000.0) * (n(1.0) / n(30000.0))));
+        assert_eq!(d.asks[2].price(), Price(n(2100.0) * (n(1.0) / n(30000.0))));
+        // sell ETH for BTC: sell ETH at its bids, buy BTC at its asks
+        assert_eq!(d.bids.len(), 1);
+        assert_eq!(d.bids[0].price(), Price(n(1900.0) * (n(1.0) / n(40000.0))));
+        assert_eq!(d.bids[0].amount(), Amount(n(1.0)));
+    }
+
+    #[test]
+    fn test_synthetic_cross_no_common() {
+        let s1 = Symbol::try_from("eg:ETH/USDT").unwrap();
+        let s2 = Symbol::try_from("eg:BTC/EUR").unwrap();
+        let d = Depth::new();
+        assert!(synthetic_cross(&s1, &d, &s2, &d).is_err());
+    }
+
+    #[test]
+    fn test_depth_invert() {
+        let d = Depth {
+            asks: vec![Liq::from_pa(Price(n(2.0)), Amount(n(1.0)))],
+            bids: vec![
+                Liq::from_pa(Price(n(1.0)), Amount(n(1.0))),
+                Liq::from_pa(Price(n(0.5)), Amount(n(4.0))),
+            ],
+        };
+        let inv = d.invert();
+        assert_eq!(inv.asks[0].price(), Price(n(1.0)));
+        assert_eq!(inv.asks[1].price(), Price(n(2.0)));
+        assert_eq!(inv.asks[1].amount(), Amount(n(2.0)));
+        assert_eq!(inv.bids[0].price(), Price(n(0.5)));
+    }
+
     #[test]
     fn test_l2_1() {
         let asks = vec![
-            Liq::from_pa(Price(1.0), Amount(2.0)),
-            Liq::from_pa(Price(1.0), Amount(0.9)),
+            Liq::from_pa(Price(n(1.0)), Amount(n(2.0))),
+            Liq::from_pa(Price(n(1.0)), Amount(n(0.9))),
         ];
         let asks_l2 = liqs_l2(&asks);
-        assert_eq!(asks_l2[0].amount(), Amount(2.9))
+        assert_eq!(asks_l2[0].amount(), Amount(n(2.9)))
     }
 
     #[test]
     fn test_l2_2() {
         let asks = vec![
-            Liq::from_pa(Price(1.0), Amount(2.0)),
-            Liq::from_pa(Price(1.0), Amount(0.9)),
-            Liq::from_pa(Price(2.0), Amount(1.0)),
+            Liq::from_pa(Price(n(1.0)), Amount(n(2.0))),
+            Liq::from_pa(Price(n(1.0)), Amount(n(0.9))),
+            Liq::from_pa(Price(n(2.0)), Amount(n(1.0))),
         ];
         let asks_l2 = liqs_l2(&asks);
-        assert_eq!(asks_l2[0].amount(), Amount(2.9));
-        assert_eq!(asks_l2[1].price(), Price(2.0));
-        assert_eq!(asks_l2[1].amount(), Amount(1.0));
+        assert_eq!(asks_l2[0].amount(), Amount(n(2.9)));
+        assert_eq!(asks_l2[1].price(), Price(n(2.0)));
+        assert_eq!(asks_l2[1].amount(), Amount(n(1.0)));
         assert!(asks_l2.len() == 2);
     }
 
     #[test]
     fn test_l2_3() {
         let asks = vec![
-            Liq::from_pa(Price(1.0), Amount(2.0)),
-            Liq::from_pa(Price(2.0), Amount(1.0)),
+            Liq::from_pa(Price(n(1.0)), Amount(n(2.0))),
+            Liq::from_pa(Price(n(2.0)), Amount(n(1.0))),
         ];
         let asks_l2 = liqs_l2(&asks);
-        assert_eq!(asks_l2[0].amount(), Amount(2.0));
-        assert_eq!(asks_l2[1].price(), Price(2.0));
-        assert_eq!(asks_l2[1].amount(), Amount(1.0));
+        assert_eq!(asks_l2[0].amount(), Amount(n(2.0)));
+        assert_eq!(asks_l2[1].price(), Price(n(2.0)));
+        assert_eq!(asks_l2[1].amount(), Amount(n(1.0)));
         assert!(asks_l2.len() == 2);
     }
 
     #[test]
     fn test_l2_4() {
-        let asks = vec![Liq::from_pa(Price(1.0), Amount(2.0))];
+        let asks = vec![Liq::from_pa(Price(n(1.0)), Amount(n(2.0)))];
         let asks_l2 = liqs_l2(&asks);
-        assert_eq!(asks_l2[0].price(), Price(1.0));
-        assert_eq!(asks_l2[0].amount(), Amount(2.0));
+        assert_eq!(asks_l2[0].price(), Price(n(1.0)));
+        assert_eq!(asks_l2[0].amount(), Amount(n(2.0)));
         assert!(asks_l2.len() == 1);
     }
 
@@ -180,6 +388,54 @@ mod test {
     fn test_l2_5() {
         let asks: Vec<Liq> = Vec::new();
         let asks_l2 = liqs_l2(&asks);
-        assert_eq!(asks_l2.is_empty(), true);
+        assert!(asks_l2.is_empty());
+    }
+
+    #[test]
+    fn test_l2_side() {
+        // non-monotonic bids: only adjacent levels are merged, the order is kept
+        let bids = vec![liq(10.0, 1.0), liq(9.0, 1.0), liq(10.0, 2.0), liq(8.0, 1.0)];
+        assert_eq!(liqs_l2(&bids), bids);
+        assert_eq!(
+            liqs_l2_side(&bids, Side::Buy),
+            vec![liq(10.0, 3.0), liq(9.0, 1.0), liq(8.0, 1.0)]
+        );
+        assert_eq!(
+            drop_worth(&bids, &[], Worth(n(10.0)))[0].price(),
+            Price(n(9.0))
+        );
+        let one = vec![liq(10.0, 1.0), liq(10.0, 1.0)];
+        assert_eq!(liqs_l2_side(&one, Side::Buy), vec![liq(10.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_slippage_bids() {
+        let bids = vec![
+            Liq::from_pa(Price(n(2.0)), Amount(n(10.0))),
+            Liq::from_pa(Price(n(1.0)), Amount(n(10.0))),
+        ];
+        assert_eq!(slippage(&bids, Worth(n(30.0))), Some(Ratio(n(0.5))));
+        let d = Depth { asks: ll(), bids };
+        assert_eq!(
+            slippage_side(&d, Side::Sell, Worth(n(30.0))),
+            Some(Ratio(n(0.5)))
+        );
+        assert_eq!(
+            slippage_side(&d, Side::Buy, Worth(n(20.0))),
+            Some(Ratio::ONE)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_l2_decimal_exact() {
+        let asks = vec![
+            Liq::from_pa(Price(n(0.1) + n(0.2)), Amount(n(0.1))),
+            Liq::from_pa(Price(n(0.3)), Amount(n(0.2))),
+        ];
+        let asks_l2 = liqs_l2(&asks);
+        assert_eq!(asks_l2.len(), 1);
+        assert_eq!(asks_l2[0].amount(), Amount(n(0.3)));
+        assert_eq!(asks_l2[0].worth(), Worth(n(0.09)));
     }
 }
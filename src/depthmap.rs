@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{Amount, Depth, Liq, Price};
+use crate::depth::{ratio_band, ticks_band};
+use crate::{num_from_f64, Amount, CrossRepair, CrossState, Depth, Liq, Price, Ratio};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct DepthMap {
-    pub asks: HashMap<Price, Liq>,
-    pub bids: HashMap<Price, Liq>,
+    pub asks: BTreeMap<Price, Liq>,
+    pub bids: BTreeMap<Price, Liq>,
+    // worst levels above it are pruned on updates (partial-book feeds), not serialized
+    #[serde(skip)]
+    max_levels: Option<usize>,
 }
 
 impl DepthMap {
@@ -15,36 +19,231 @@ impl DepthMap {
         Self {
             asks: Default::default(),
             bids: Default::default(),
+            max_levels: None,
         }
     }
 
+    /// Depth map keeping at most max_levels of each side
+    pub fn with_max_levels(max_levels: usize) -> Self {
+        Self {
+            max_levels: Some(max_levels),
+            ..Self::new()
+        }
+    }
+
+    pub fn max_levels(&self) -> Option<usize> {
+        self.max_levels
+    }
+
+    /// Keeps at most max_levels of each side from now on, prunes the excess right away
+    pub fn set_max_levels(&mut self, max_levels: usize) {
+        self.max_levels = Some(max_levels);
+        self.prune();
+    }
+
     pub fn replace(&mut self, other: Self) {
+        let max_levels = self.max_levels;
         *self = other;
+        self.max_levels = self.max_levels.or(max_levels);
+        self.prune();
     }
 
     pub fn into_depth(self) -> Depth {
         let mut d = Depth::new();
-        d.asks = self.asks.into_values().collect();
-        d.bids = self.bids.into_values().collect();
-        d.asks.sort_by(|x, y| x.p.partial_cmp(&y.p).unwrap()); // ascending
-        d.bids.sort_by(|x, y| y.p.partial_cmp(&x.p).unwrap()); // descending
+        d.asks = self.asks.into_values().collect(); // ascending
+        d.bids = self.bids.into_values().rev().collect(); // descending
         d
     }
 
+    pub fn best_bid(&self) -> Option<Price> {
+        self.bids.last_key_value().map(|(&p, _)| p)
+    }
+
+    pub fn best_ask(&self) -> Option<Price> {
+        self.asks.first_key_value().map(|(&p, _)| p)
+    }
+
+    pub fn cross_state(&self) -> CrossState {
+        CrossState::from_best(self.best_bid(), self.best_ask())
+    }
+
+    /// Drops crossing (and locking) levels of the stale side,
+    /// returns the number of dropped levels and the state after repair
+    /// (still crossed or locked if the stale side is unknown)
+    pub fn repair_cross(&mut self, repair: CrossRepair) -> (usize, CrossState) {
+        let (Some(best_bid), Some(best_ask)) = (self.best_bid(), self.best_ask()) else {
+            return (0, self.cross_state());
+        };
+        let (trim_bids, trim_asks) = repair.stale_sides();
+        let len = self.bids.len() + self.asks.len();
+        if trim_bids {
+            self.bids.retain(|&p, _| p < best_ask);
+        }
+        if trim_asks {
+            self.asks.retain(|&p, _| p > best_bid);
+        }
+        (len - self.bids.len() - self.asks.len(), self.cross_state())
+    }
+
     pub fn update(&mut self, other: &Self) {
         other.asks.iter().for_each(|(&p, &l)| {
-            if l.a == Amount(0.0) {
+            if l.a == Amount::ZERO {
                 self.asks.remove(&p);
             } else {
                 self.asks.entry(p).and_modify(|e| *e = l).or_insert(l);
             }
         });
         other.bids.iter().for_each(|(&p, &l)| {
-            if l.a == Amount(0.0) {
+            if l.a == Amount::ZERO {
                 self.bids.remove(&p);
             } else {
                 self.bids.entry(p).and_modify(|e| *e = l).or_insert(l);
             }
         });
+        self.prune();
+    }
+
+    /// Replaces only the window covered by the partial (top-N) snapshot:
+    /// levels from the top to the worst snapshot level of each non-empty side.
+    /// Levels beyond the window are kept (until pruned by max_levels)
+    pub fn update_partial(&mut self, snapshot: &Self) {
+        if let Some(&worst) = snapshot.asks.keys().next_back() {
+            self.asks.retain(|&p, _| p > worst);
+        }
+        if let Some(&worst) = snapshot.bids.keys().next() {
+            self.bids.retain(|&p, _| p < worst);
+        }
+        self.update(snapshot);
+    }
+
+    /// Keeps top n levels of each side, the worst ones are popped from the map ends
+    pub fn truncate(&mut self, n: usize) {
+        while self.asks.len() > n {
+            self.asks.pop_last();
+        }
+        while self.bids.len() > n {
+            self.bids.pop_first();
+        }
+    }
+
+    pub fn mid(&self) -> Option<Price> {
+        Some((self.best_bid()? + self.best_ask()?) / num_from_f64(2.0))
+    }
+
+    /// Keeps levels with prices within [lo, hi]
+    pub fn retain_band(&mut self, lo: Price, hi: Price) {
+        self.asks.retain(|&p, _| p >= lo && p <= hi);
+        self.bids.retain(|&p, _| p >= lo && p <= hi);
+    }
+
+    /// Keeps levels within the ratio (e.g. 1%) around mid, no-op for one-sided depth
+    pub fn retain_ratio_band(&mut self, r: Ratio) {
+        if let Some(mid) = self.mid() {
+            let (lo, hi) = ratio_band(mid, r);
+            self.retain_band(lo, hi);
+        }
+    }
+
+    /// Keeps levels within n ticks around mid, no-op for one-sided depth
+    pub fn retain_ticks_band(&mut self, ticks: usize, tick: Price) {
+        if let Some(mid) = self.mid() {
+            let (lo, hi) = ticks_band(mid, ticks, tick);
+            self.retain_band(lo, hi);
+        }
+    }
+
+    // private helpers
+
+    fn prune(&mut self) {
+        if let Some(max_levels) = self.max_levels {
+            self.truncate(max_levels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{liq, n, Side};
+
+    #[test]
+    fn test_depthmap_repair_cross() {
+        let mut m = Depth {
+            asks: vec![liq(10.0, 1.0), liq(11.0, 1.0)],
+            bids: vec![liq(10.0, 1.0), liq(9.0, 1.0)],
+        }
+        .into_depthmap();
+        assert_eq!(m.cross_state(), CrossState::Locked);
+        assert_eq!(
+            m.repair_cross(CrossRepair::TrustLatest(Side::Sell)),
+            (1, CrossState::Normal)
+        );
+        assert_eq!(m.best_bid(), Some(Price(n(9.0))));
+    }
+
+    #[test]
+    fn test_depthmap_max_levels() {
+        let mut m = DepthMap::with_max_levels(2);
+        m.update(
+            &Depth {
+                asks: vec![liq(13.0, 1.0), liq(11.0, 1.0), liq(12.0, 1.0)],
+                bids: vec![liq(10.0, 1.0), liq(8.0, 1.0), liq(9.0, 1.0)],
+            }
+            .into_depthmap(),
+        );
+        let d = m.clone().into_depth();
+        assert_eq!(d.asks, vec![liq(11.0, 1.0), liq(12.0, 1.0)]);
+        assert_eq!(d.bids, vec![liq(10.0, 1.0), liq(9.0, 1.0)]);
+        m.update_partial(
+            &Depth {
+                asks: vec![liq(11.5, 1.0)],
+                bids: vec![],
+            }
+            .into_depthmap(),
+        );
+        assert_eq!(
+            m.clone().into_depth().asks,
+            vec![liq(11.5, 1.0), liq(12.0, 1.0)]
+        );
+        m.retain_ticks_band(2, Price(n(0.5)));
+        assert_eq!(m.clone().into_depth().asks, vec![liq(11.5, 1.0)]);
+        assert_eq!(m.clone().into_depth().bids, vec![liq(10.0, 1.0)]);
+        m.set_max_levels(1);
+        m.update(&Depth::new().into_depthmap());
+        assert_eq!(m.max_levels(), Some(1));
+        let bytes = bincode::serialize(&m).unwrap();
+        let m2: DepthMap = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(m2.max_levels(), None);
+        assert_eq!(m2.into_depth().asks, vec![liq(11.5, 1.0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_depthmap_update_decimal_exact() {
+        let mut m = Depth {
+            asks: vec![Liq::from_pa(Price(n(0.3)), Amount(n(1.0)))],
+            bids: vec![],
+        }
+        .into_depthmap();
+        m.update(
+            &Depth {
+                asks: vec![Liq::from_pa(
+                    Price(n(0.1) + n(0.2)),
+                    Amount(n(0.1) + n(0.2)),
+                )],
+                bids: vec![],
+            }
+            .into_depthmap(),
+        );
+        let d = m.clone().into_depth();
+        assert_eq!(d.asks, vec![Liq::from_pa(Price(n(0.3)), Amount(n(0.3)))]);
+        m.update(
+            &Depth {
+                asks: vec![Liq::from_pa(Price(n(0.1) + n(0.2)), Amount::ZERO)],
+                bids: vec![],
+            }
+            .into_depthmap(),
+        );
+        assert!(m.asks.is_empty());
     }
 }
@@ -1,39 +1,141 @@
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::hash::Hash;
 
 use serde::{Deserialize, Serialize};
 
 use crate::{Depth, DepthMap};
 
-/// Special structure suitable to be a mutex-protected storage for depths of different sources (pairs)
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
-pub struct Depths {
-    pub vecs: HashMap<String, Depth>, // key can be any, usually str(symbol)
-    pub maps: HashMap<String, DepthMap>, // key can be any, usually str(symbol)
+/// Special structure suitable to be a mutex-protected storage for depths of different sources (pairs).
+/// Key can be any, usually str(symbol) or SymbolId to avoid string hashing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "K: Serialize + Eq + Hash",
+    deserialize = "K: Deserialize<'de> + Eq + Hash"
+))]
+pub struct Depths<K = String> {
+    pub vecs: HashMap<K, Depth>,
+    pub maps: HashMap<K, DepthMap>,
 }
 
-impl Depths {
-    pub fn new() -> Depths {
+impl<K> Default for Depths<K> {
+    fn default() -> Self {
+        Self {
+            vecs: Default::default(),
+            maps: Default::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> Depths<K> {
+    pub fn new() -> Depths<K> {
         Depths {
             vecs: Default::default(),
             maps: Default::default(),
         }
     }
 
-    pub fn upsert(&mut self, key: &str, depth: Depth) {
-        let depth_map = depth.clone().into_depthmap();
-        self.maps.entry(key.into()).or_default().replace(depth_map);
-        self.vecs.entry(key.into()).or_default().replace(depth);
+    /// Replaces the depth (snapshot), the vec view is stored sorted.
+    /// Both views keep max_levels of the map if it's set
+    pub fn upsert<Q>(&mut self, key: &Q, depth: Depth)
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+    {
+        let depth_map = self.maps.entry(key.to_owned()).or_default();
+        depth_map.replace(depth.into_depthmap());
+        let depth = depth_map.clone().into_depth();
+        self.vecs.entry(key.to_owned()).or_default().replace(depth);
     }
 
-    pub fn update(&mut self, key: &str, depth_change: Depth) {
-        let depth_map = depth_change.clone().into_depthmap();
-        self.maps
-            .entry(key.into())
-            .and_modify(|x| x.update(&depth_map))
-            .or_insert(depth_map);
+    /// Applies depth change (delta), the sorted vec view is updated incrementally
+    /// and pruned to max_levels of the map as the map is
+    pub fn update<Q>(&mut self, key: &Q, depth_change: Depth)
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+    {
+        let depth = self.vecs.entry(key.to_owned()).or_default();
+        depth.update(&depth_change);
+        let depth_map = self.maps.entry(key.to_owned()).or_default();
+        depth_map.update(&depth_change.into_depthmap());
+        if let Some(max_levels) = depth_map.max_levels() {
+            depth.truncate(max_levels);
+        }
+    }
+
+    /// Limits both views of the depth to top max_levels of each side (partial-book feeds)
+    pub fn set_max_levels<Q>(&mut self, key: &Q, max_levels: usize)
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+    {
+        let depth_map = self.maps.entry(key.to_owned()).or_default();
+        depth_map.set_max_levels(max_levels);
         self.vecs
-            .entry(key.into())
-            .and_modify(|x| *x = self.maps.get(key).unwrap().clone().into_depth())
-            .or_insert(depth_change);
+            .entry(key.to_owned())
+            .or_default()
+            .truncate(max_levels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::liq;
+
+    #[test]
+    fn test_depths_update_in_sync() {
+        let mut dd: Depths = Depths::new();
+        dd.upsert(
+            "eg:BTC/USDT",
+            Depth {
+                asks: vec![liq(12.0, 1.0), liq(11.0, 1.0)],
+                bids: vec![liq(10.0, 1.0)],
+            },
+        );
+        dd.update(
+            "eg:BTC/USDT",
+            Depth {
+                asks: vec![liq(11.0, 0.0), liq(13.0, 1.0)],
+                bids: vec![liq(10.5, 1.0)],
+            },
+        );
+        let d = &dd.vecs["eg:BTC/USDT"];
+        assert_eq!(d.asks, vec![liq(12.0, 1.0), liq(13.0, 1.0)]);
+        assert_eq!(d.bids, vec![liq(10.5, 1.0), liq(10.0, 1.0)]);
+        let from_map = dd.maps["eg:BTC/USDT"].clone().into_depth();
+        assert_eq!(from_map.asks, d.asks);
+        assert_eq!(from_map.bids, d.bids);
+    }
+
+    #[test]
+    fn test_depths_max_levels_in_sync() {
+        let mut dd: Depths = Depths::new();
+        dd.set_max_levels("eg:BTC/USDT", 2);
+        dd.upsert(
+            "eg:BTC/USDT",
+            Depth {
+                asks: vec![liq(11.0, 1.0), liq(12.0, 1.0), liq(13.0, 1.0)],
+                bids: vec![liq(10.0, 1.0), liq(9.0, 1.0), liq(8.0, 1.0)],
+            },
+        );
+        assert_eq!(
+            dd.vecs["eg:BTC/USDT"].asks,
+            vec![liq(11.0, 1.0), liq(12.0, 1.0)]
+        );
+        dd.update(
+            "eg:BTC/USDT",
+            Depth {
+                asks: vec![liq(10.5, 1.0)],
+                bids: vec![liq(9.5, 1.0)],
+            },
+        );
+        let d = &dd.vecs["eg:BTC/USDT"];
+        assert_eq!(d.asks, vec![liq(10.5, 1.0), liq(11.0, 1.0)]);
+        assert_eq!(d.bids, vec![liq(10.0, 1.0), liq(9.5, 1.0)]);
+        let from_map = dd.maps["eg:BTC/USDT"].clone().into_depth();
+        assert_eq!(from_map.asks, d.asks);
+        assert_eq!(from_map.bids, d.bids);
     }
 }
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, Currency, Exchange, Liq, Money, Price, Ratio, Side, Symbol, Trade, Worth};
+
+/// Role of the order in the fill: maker (was in the book) or taker
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeRole {
+    #[serde(rename = "maker", alias = "Maker", alias = "MAKER")]
+    Maker,
+    #[serde(rename = "taker", alias = "Taker", alias = "TAKER")]
+    Taker,
+}
+
+/// Currency the fee is charged in
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeCurrency {
+    Base,
+    Quote,
+    Other(Currency), // third token, e.g. BNB
+}
+
+/// Maker and taker rates of a VIP tier, negative rate is a rebate
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FeeRates {
+    pub maker: Ratio,
+    pub taker: Ratio,
+}
+
+/// Fee schedule of an exchange or a symbol:
+/// rates by VIP tier (tiers[0] is the regular one), current tier and fee currency
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeeSchedule {
+    pub tiers: Vec<FeeRates>,
+    pub tier: usize,
+    pub currency: FeeCurrency,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum FeeError {
+    #[error("no fee tiers")]
+    NoTiers,
+    #[error("no fee tier {0}")]
+    BadTier(usize),
+    #[error("no price of quote currency in fee currency {0}")]
+    NoTokenPrice(Currency),
+}
+
+/// Fill with fees applied.
+/// amount: base currency received on buy or spent on sell,
+/// worth: quote currency paid on buy or received on sell
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetFill {
+    pub amount: Amount,
+    pub worth: Worth,
+    pub fee: Money,
+}
+
+impl FeeSchedule {
+    pub fn new(maker: Ratio, taker: Ratio, currency: FeeCurrency) -> Self {
+        Self {
+            tiers: vec![FeeRates { maker, taker }],
+            tier: 0,
+            currency,
+        }
+    }
+
+    /// Rate of the current tier
+    pub fn rate(&self, role: FeeRole) -> Result<Ratio, FeeError> {
+        if self.tiers.is_empty() {
+            return Err(FeeError::NoTiers);
+        }
+        let rates = self
+            .tiers
+            .get(self.tier)
+            .ok_or(FeeError::BadTier(self.tier))?;
+        Ok(match role {
+            FeeRole::Maker => rates.maker,
+            FeeRole::Taker => rates.taker,
+        })
+    }
+
+    /// Fee of the liq in the fee currency.
+    /// token_price is the price of quote currency in the third token,
+    /// required only for FeeCurrency::Other
+    pub fn fee(
+        &self,
+        symbol: &Symbol,
+        liq: &Liq,
+        role: FeeRole,
+        token_price: Option<Price>,
+    ) -> Result<Money, FeeError> {
+        let rate = self.rate(role)?;
+        match &self.currency {
+            FeeCurrency::Base => Ok(Money::base(symbol, liq.amount() * rate)),
+            FeeCurrency::Quote => Ok(Money::quote(symbol, liq.worth() * rate)),
+            FeeCurrency::Other(c) => {
+                let p = token_price.ok_or_else(|| FeeError::NoTokenPrice(c.to_uppercase()))?;
+                Ok(Money::new(c.clone(), (liq.worth() * rate).0 * p.0))
+            }
+        }
+    }
+
+    /// Net amount and worth of the liq after the fee
+    pub fn net_fill(
+        &self,
+        symbol: &Symbol,
+        liq: &Liq,
+        side: Side,
+        role: FeeRole,
+        token_price: Option<Price>,
+    ) -> Result<NetFill, FeeError> {
+        let fee = self.fee(symbol, liq, role, token_price)?;
+        let (mut amount, mut worth) = (liq.amount(), liq.worth());
+        match (&self.currency, side) {
+            (FeeCurrency::Base, Side::Buy) => amount -= fee.amount(),
+            (FeeCurrency::Base, Side::Sell) => amount += fee.amount(),
+            (FeeCurrency::Quote, Side::Buy) => worth += fee.worth(),
+            (FeeCurrency::Quote, Side::Sell) => worth -= fee.worth(),
+            (FeeCurrency::Other(_), _) => {}
+        }
+        Ok(NetFill { amount, worth, fee })
+    }
+
+    /// Net amount and worth of the trade after the fee
+    pub fn net_trade(
+        &self,
+        trade: &Trade,
+        role: FeeRole,
+        token_price: Option<Price>,
+    ) -> Result<NetFill, FeeError> {
+        self.net_fill(&trade.symbol, &trade.liq, trade.side, role, token_price)
+    }
+}
+
+/// Fee schedules by exchange with overrides by symbol
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FeeSchedules {
+    pub exchanges: HashMap<Exchange, FeeSchedule>,
+    pub symbols: HashMap<String, FeeSchedule>, // key is str(symbol)
+}
+
+impl FeeSchedules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule of the symbol, or of its exchange if there is no symbol override
+    pub fn get(&self, symbol: &Symbol) -> Option<&FeeSchedule> {
+        self.symbols
+            .get(&symbol.to_string())
+            .or_else(|| self.exchanges.get(&symbol.eg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{btcusdt, n};
+
+    fn liq() -> Liq {
+        Liq::from_pa(Price(n(100.0)), Amount(n(2.0)))
+    }
+
+    #[test]
+    fn test_fee_quote() {
+        let fs = FeeSchedule::new(
+            Ratio::from_bps(n(-1.0)),
+            Ratio::from_bps(n(10.0)),
+            FeeCurrency::Quote,
+        );
+        let nf = fs
+            .net_fill(&btcusdt(), &liq(), Side::Buy, FeeRole::Taker, None)
+            .unwrap();
+        assert_eq!(nf.fee, Money::new("USDT".into(), n(0.2)));
+        assert_eq!(nf.worth, Worth(n(200.2)));
+        assert_eq!(nf.amount, Amount(n(2.0)));
+        let nf = fs
+            .net_fill(&btcusdt(), &liq(), Side::Sell, FeeRole::Maker, None)
+            .unwrap();
+        assert_eq!(nf.worth, Worth(n(200.02)));
+    }
+
+    #[test]
+    fn test_fee_base_vip() {
+        let mut fs = FeeSchedule::new(
+            Ratio::from_bps(n(10.0)),
+            Ratio::from_bps(n(10.0)),
+            FeeCurrency::Base,
+        );
+        fs.tiers.push(FeeRates {
+            maker: Ratio::from_bps(n(5.0)),
+            taker: Ratio::from_bps(n(5.0)),
+        });
+        fs.tier = 5;
+        assert_eq!(fs.rate(FeeRole::Maker), Err(FeeError::BadTier(5)));
+        fs.tier = 1;
+        let nf = fs
+            .net_fill(&btcusdt(), &liq(), Side::Buy, FeeRole::Maker, None)
+            .unwrap();
+        assert_eq!(nf.amount, Amount(n(1.999)));
+    }
+
+    #[test]
+    fn test_fee_other() {
+        let fs = FeeSchedule::new(
+            Ratio::from_bps(n(10.0)),
+            Ratio::from_bps(n(10.0)),
+            FeeCurrency::Other("bnb".into()),
+        );
+        let r = fs.fee(&btcusdt(), &liq(), FeeRole::Taker, None);
+        assert_eq!(r, Err(FeeError::NoTokenPrice("BNB".into())));
+        let fee = fs
+            .fee(&btcusdt(), &liq(), FeeRole::Taker, Some(Price(n(0.5))))
+            .unwrap();
+        assert_eq!(fee, Money::new("BNB".into(), n(0.1)));
+    }
+
+    #[test]
+    fn test_fee_schedules() {
+        let mut fss = FeeSchedules::new();
+        let fs = FeeSchedule::new(Ratio::ZERO, Ratio::ZERO, FeeCurrency::Quote);
+        fss.exchanges.insert("eg".into(), fs.clone());
+        assert_eq!(fss.get(&btcusdt()), Some(&fs));
+        let json = serde_json::to_string(&fss).unwrap();
+        assert!(json.contains(r#""currency":"quote""#));
+    }
+}
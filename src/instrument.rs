@@ -0,0 +1,269 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{num_from_str, Currency, Exchange, Price, Symbol, SymbolError};
+
+static EXPIRY_FORMAT: &str = "%y%m%d";
+
+/// Call or put option
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OptionRight {
+    #[serde(rename = "call", alias = "Call", alias = "C")]
+    Call,
+    #[serde(rename = "put", alias = "Put", alias = "P")]
+    Put,
+}
+
+/// Kind of instrument with its derivative-specific data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum InstrumentKind {
+    Spot,
+    Perp {
+        settle: Currency,
+    },
+    Future {
+        settle: Currency,
+        expiry: NaiveDate,
+    },
+    Option {
+        settle: Currency,
+        expiry: NaiveDate,
+        strike: Price,
+        right: OptionRight,
+    },
+}
+
+/// Instrument extends Symbol (spot pair) with derivative kind.
+/// Text form: "eg:BTC/USDT" (spot), "eg:BTC/USDT:USDT" (perp),
+/// "eg:BTC/USD:BTC-231229" (future), "eg:BTC/USD:BTC-231229-30000-C" (option)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instrument {
+    pub symbol: Symbol,
+    pub kind: InstrumentKind,
+}
+
+impl Instrument {
+    pub fn spot(symbol: Symbol) -> Self {
+        Self {
+            symbol,
+            kind: InstrumentKind::Spot,
+        }
+    }
+
+    pub fn perp(symbol: Symbol, settle: Currency) -> Self {
+        Self {
+            symbol,
+            kind: InstrumentKind::Perp {
+                settle: settle.to_uppercase(),
+            },
+        }
+    }
+
+    pub fn future(symbol: Symbol, settle: Currency, expiry: NaiveDate) -> Self {
+        Self {
+            symbol,
+            kind: InstrumentKind::Future {
+                settle: settle.to_uppercase(),
+                expiry,
+            },
+        }
+    }
+
+    pub fn option(
+        symbol: Symbol,
+        settle: Currency,
+        expiry: NaiveDate,
+        strike: Price,
+        right: OptionRight,
+    ) -> Self {
+        Self {
+            symbol,
+            kind: InstrumentKind::Option {
+                settle: settle.to_uppercase(),
+                expiry,
+                strike,
+                right,
+            },
+        }
+    }
+
+    pub fn is_spot(&self) -> bool {
+        matches!(self.kind, InstrumentKind::Spot)
+    }
+
+    pub fn settle(&self) -> Option<&Currency> {
+        match &self.kind {
+            InstrumentKind::Spot => None,
+            InstrumentKind::Perp { settle }
+            | InstrumentKind::Future { settle, .. }
+            | InstrumentKind::Option { settle, .. } => Some(settle),
+        }
+    }
+
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        match &self.kind {
+            InstrumentKind::Future { expiry, .. } | InstrumentKind::Option { expiry, .. } => {
+                Some(*expiry)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<Symbol> for Instrument {
+    fn from(symbol: Symbol) -> Self {
+        Self::spot(symbol)
+    }
+}
+
+impl std::fmt::Display for Instrument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol)?;
+        match &self.kind {
+            InstrumentKind::Spot => Ok(()),
+            InstrumentKind::Perp { settle } => write!(f, ":{}", settle),
+            InstrumentKind::Future { settle, expiry } => {
+                write!(f, ":{}-{}", settle, expiry.format(EXPIRY_FORMAT))
+            }
+            InstrumentKind::Option {
+                settle,
+                expiry,
+                strike,
+                right,
+            } => {
+                let right = match right {
+                    OptionRight::Call => "C",
+                    OptionRight::Put => "P",
+                };
+                let expiry = expiry.format(EXPIRY_FORMAT);
+                write!(f, ":{}-{}-{}-{}", settle, expiry, strike.0, right)
+            }
+        }
+    }
+}
+
+impl TryFrom<(Exchange, &str)> for Instrument {
+    type Error = SymbolError;
+
+    fn try_from((eg, text): (Exchange, &str)) -> Result<Self, Self::Error> {
+        let bad = || SymbolError::BadSymbol(text.to_string());
+        let Some((pair, deriv)) = text.split_once(':') else {
+            return Ok(Self::spot(Symbol::try_from((eg, text))?));
+        };
+        let symbol = Symbol::try_from((eg, pair))?;
+        let parts = deriv.split('-').collect::<Vec<&str>>();
+        let expiry = || NaiveDate::parse_from_str(parts[1], EXPIRY_FORMAT).map_err(|_| bad());
+        match parts.len() {
+            1 if !parts[0].is_empty() => Ok(Self::perp(symbol, parts[0].to_string())),
+            2 => Ok(Self::future(symbol, parts[0].to_string(), expiry()?)),
+            4 => {
+                let strike = num_from_str(parts[2]).map_err(|_| bad())?;
+                let strike = Price::new(strike).map_err(|_| bad())?;
+                let right = match parts[3] {
+                    "C" | "c" => OptionRight::Call,
+                    "P" | "p" => OptionRight::Put,
+                    _ => return Err(bad()),
+                };
+                Ok(Self::option(
+                    symbol,
+                    parts[0].to_string(),
+                    expiry()?,
+                    strike,
+                    right,
+                ))
+            }
+            _ => Err(bad()),
+        }
+    }
+}
+
+impl TryFrom<&str> for Instrument {
+    type Error = SymbolError;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        let Some((eg, rest)) = text.split_once(':') else {
+            return Err(SymbolError::BadSymbol(text.to_string()));
+        };
+        Self::try_from((eg.to_string(), rest))
+    }
+}
+
+impl TryFrom<String> for Instrument {
+    type Error = SymbolError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::try_from(text.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{n, Amount, Liq, Side, Trade};
+
+    #[test]
+    fn test_trade_instrument() {
+        let i = Instrument::try_from("eg:BTC/USDT:USDT").unwrap();
+        let mut t = Trade {
+            id: "t1".into(),
+            symbol: i.symbol.clone(),
+            kind: Some(i.kind.clone()),
+            liq: Liq::from_pa(Price(n(1.0)), Amount(n(2.0))),
+            side: Side::Buy,
+            ts: chrono::Utc::now(),
+        };
+        assert_eq!(t.instrument(), i);
+        assert_eq!(json!(t)["kind"]["type"], "perp");
+        t.kind = None;
+        assert!(t.instrument().is_spot());
+        assert!(json!(t).get("kind").is_none());
+    }
+
+    fn round_trip(text: &str) -> Instrument {
+        let i = Instrument::try_from(text).unwrap();
+        assert_eq!(i.to_string(), text);
+        i
+    }
+
+    #[test]
+    fn test_instrument_round_trip() {
+        assert!(round_trip("eg:BTC/USDT").is_spot());
+        assert_eq!(
+            round_trip("eg:BTC/USDT:USDT").settle(),
+            Some(&"USDT".into())
+        );
+        let f = round_trip("eg:BTC/USD:BTC-231229");
+        assert_eq!(f.expiry(), NaiveDate::from_ymd_opt(2023, 12, 29));
+        let o = round_trip("eg:BTC/USD:BTC-231229-30000.5-P");
+        assert!(matches!(
+            o.kind,
+            InstrumentKind::Option {
+                right: OptionRight::Put,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_instrument_bad() {
+        assert!(Instrument::try_from("eg:BTC/USD:").is_err());
+        assert!(Instrument::try_from("eg:BTC/USD:BTC-2312").is_err());
+        assert!(Instrument::try_from("eg:BTC/USD:BTC-231229-x-C").is_err());
+        assert!(Instrument::try_from("eg:BTC/USD:BTC-231229-1-X").is_err());
+    }
+
+    #[test]
+    fn test_instrument_to_json() {
+        let i = Instrument::try_from("eg:BTC/USDT:USDT").unwrap();
+        let v = json!(i);
+        assert_eq!(
+            v.to_string(),
+            r#"{"kind":{"settle":"USDT","type":"perp"},"symbol":{"bs":"BTC","eg":"eg","qt":"USDT"}}"#
+        );
+        let i2: Instrument = serde_json::from_value(v).unwrap();
+        assert_eq!(i, i2);
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    num_ceil_to_step, num_floor_to_step, num_is_on_step, num_precision, num_to_string_prec, Amount,
+    Liq, OrderToPlace, Price, Side, Symbol, Worth,
+};
+
+/// Trading rules of a symbol on its exchange: tick size, lot step and limits.
+/// Zero tick size or lot step means no rounding
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstrumentSpec {
+    pub symbol: Symbol,
+    pub tick_size: Price,
+    pub lot_step: Amount,
+    pub min_amount: Amount,
+    pub max_amount: Option<Amount>,
+    pub min_notional: Worth,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum InstrumentSpecError {
+    #[error("price {0:?} is not a multiple of tick size {1:?}")]
+    BadTick(Price, Price),
+    #[error("amount {0:?} is not a multiple of lot step {1:?}")]
+    BadStep(Amount, Amount),
+    #[error("amount {0:?} is less than min amount {1:?}")]
+    AmountTooSmall(Amount, Amount),
+    #[error("amount {0:?} is greater than max amount {1:?}")]
+    AmountTooLarge(Amount, Amount),
+    #[error("worth {0:?} is less than min notional {1:?}")]
+    NotionalTooSmall(Worth, Worth),
+}
+
+impl InstrumentSpec {
+    pub fn new(symbol: Symbol, tick_size: Price, lot_step: Amount) -> Self {
+        Self {
+            symbol,
+            tick_size,
+            lot_step,
+            min_amount: Amount::ZERO,
+            max_amount: None,
+            min_notional: Worth::ZERO,
+        }
+    }
+
+    /// Rounds price to tick size: bid (buy) down and ask (sell) up,
+    /// so the rounded price is never more aggressive than the origin one
+    pub fn round_price(&self, p: Price, side: Side) -> Price {
+        match side {
+            Side::Buy => Price(num_floor_to_step(p.0, self.tick_size.0)),
+            Side::Sell => Price(num_ceil_to_step(p.0, self.tick_size.0)),
+        }
+    }
+
+    /// Rounds amount to lot step toward zero
+    pub fn round_amount(&self, a: Amount) -> Amount {
+        Amount(num_floor_to_step(a.0, self.lot_step.0))
+    }
+
+    /// Rounds price and amount of the liq, worth is recalculated
+    pub fn round_liq(&self, liq: Liq, side: Side) -> Liq {
+        Liq::from_pa(
+            self.round_price(liq.price(), side),
+            self.round_amount(liq.amount()),
+        )
+    }
+
+    /// Checks the liq follows the trading rules
+    pub fn validate(&self, liq: &Liq) -> Result<(), InstrumentSpecError> {
+        let (p, a, w) = (liq.price(), liq.amount(), liq.worth());
+        if !num_is_on_step(p.0, self.tick_size.0) {
+            return Err(InstrumentSpecError::BadTick(p, self.tick_size));
+        }
+        if !num_is_on_step(a.0, self.lot_step.0) {
+            return Err(InstrumentSpecError::BadStep(a, self.lot_step));
+        }
+        if a < self.min_amount {
+            return Err(InstrumentSpecError::AmountTooSmall(a, self.min_amount));
+        }
+        if let Some(max_amount) = self.max_amount {
+            if a > max_amount {
+                return Err(InstrumentSpecError::AmountTooLarge(a, max_amount));
+            }
+        }
+        if w < self.min_notional {
+            return Err(InstrumentSpecError::NotionalTooSmall(w, self.min_notional));
+        }
+        Ok(())
+    }
+
+    /// Decimal places of prices, defined by tick size
+    pub fn price_precision(&self) -> usize {
+        num_precision(self.tick_size.0)
+    }
+
+    /// Decimal places of amounts, defined by lot step
+    pub fn amount_precision(&self) -> usize {
+        num_precision(self.lot_step.0)
+    }
+
+    /// Price as the exchange formats it: "27123.10" for tick size 0.01
+    pub fn price_to_string(&self, p: Price) -> String {
+        num_to_string_prec(p.0, self.price_precision())
+    }
+
+    /// Amount as the exchange formats it: "0.012" for lot step 0.001
+    pub fn amount_to_string(&self, a: Amount) -> String {
+        num_to_string_prec(a.0, self.amount_precision())
+    }
+
+    /// Rounds the liq and makes an order of it if it's valid
+    pub fn order_to_place(
+        &self,
+        liq: Liq,
+        side: Side,
+    ) -> Result<OrderToPlace, InstrumentSpecError> {
+        let liq = self.round_liq(liq, side);
+        self.validate(&liq)?;
+        Ok(OrderToPlace::new(liq, side))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::n;
+
+    fn spec() -> InstrumentSpec {
+        let mut s = InstrumentSpec::new(
+            Symbol::new("eg".into(), "btc".into(), "usdt".into()),
+            Price(n(0.1)),
+            Amount(n(0.001)),
+        );
+        s.min_amount = Amount(n(0.001));
+        s.max_amount = Some(Amount(n(100.0)));
+        s.min_notional = Worth(n(5.0));
+        s
+    }
+
+    #[test]
+    fn test_round_price() {
+        let s = spec();
+        assert_eq!(
+            s.round_price(Price(n(27123.15)), Side::Buy),
+            Price(n(27123.1))
+        );
+        assert_eq!(
+            s.round_price(Price(n(27123.15)), Side::Sell),
+            Price(n(27123.2))
+        );
+        assert_eq!(
+            s.round_price(Price(n(27123.1)), Side::Sell),
+            Price(n(27123.1))
+        );
+    }
+
+    #[test]
+    fn test_round_amount() {
+        let s = spec();
+        assert_eq!(s.round_amount(Amount(n(0.0129))), Amount(n(0.012)));
+        assert_eq!(s.round_amount(Amount(n(0.012))), Amount(n(0.012)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let s = spec();
+        let liq = Liq::from_pa(Price(n(27123.1)), Amount(n(0.012)));
+        assert!(s.validate(&liq).is_ok());
+        let liq = Liq::from_pa(Price(n(27123.15)), Amount(n(0.012)));
+        assert!(matches!(
+            s.validate(&liq),
+            Err(InstrumentSpecError::BadTick(..))
+        ));
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(0.001)));
+        assert!(matches!(
+            s.validate(&liq),
+            Err(InstrumentSpecError::NotionalTooSmall(..))
+        ));
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(101.0)));
+        assert!(matches!(
+            s.validate(&liq),
+            Err(InstrumentSpecError::AmountTooLarge(..))
+        ));
+    }
+
+    #[test]
+    fn test_round_btc_steps() {
+        let mut s = spec();
+        s.lot_step = Amount(n(0.00000001));
+        s.min_amount = Amount::ZERO;
+        assert_eq!(s.round_amount(Amount(n(1.23456789))), Amount(n(1.23456789)));
+        assert_eq!(
+            s.round_amount(Amount(n(0.123456789))),
+            Amount(n(0.12345678))
+        );
+        s.tick_size = Price(n(0.0001));
+        let p = Price(n(99999.9999));
+        assert_eq!(s.round_price(p, Side::Buy), p);
+        assert_eq!(s.round_price(p, Side::Sell), p);
+        assert!(s.validate(&Liq::from_pa(p, Amount(n(1.23456789)))).is_ok());
+        s.tick_size = Price(n(0.01));
+        assert_eq!(
+            s.round_price(Price(n(65000.005)), Side::Buy),
+            Price(n(65000.0))
+        );
+    }
+
+    #[test]
+    fn test_to_string() {
+        let mut s = spec();
+        s.tick_size = Price(n(0.01));
+        let p: Price = serde_json::from_str(r#""27123.10""#).unwrap();
+        assert_eq!(s.price_to_string(p + Price(n(0.0))), "27123.10");
+        assert_eq!(s.amount_to_string(Amount(n(0.1) + n(0.2))), "0.300");
+        assert_eq!(s.price_precision(), 2);
+    }
+
+    #[test]
+    fn test_order_to_place() {
+        let s = spec();
+        let o = s
+            .order_to_place(
+                Liq::from_pa(Price(n(27123.15)), Amount(n(0.0129))),
+                Side::Sell,
+            )
+            .unwrap();
+        assert_eq!(o.liq.price(), Price(n(27123.2)));
+        assert_eq!(o.liq.amount(), Amount(n(0.012)));
+    }
+}
@@ -0,0 +1,397 @@
+//! Interned currencies, exchanges and symbols for hot-path hashing.
+//!
+//! Ids are cheap `Copy` handles into a global interner, interned values live
+//! for the whole process (there are not so many currencies and symbols).
+//! Each interner holds at most MAX_INTERNED values, so untrusted input
+//! (e.g. a feed with garbage symbols) can't grow the memory without bound:
+//! deserialize fails and `new` panics when the interner is full.
+//! Ids keep references to the interned values, so only interning locks.
+//! Display and serde formats are the same as of the origin types.
+//! Trade and OrderPlaced keep owned Symbol, use SymbolId in hot-path structures.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{OnceLock, RwLock};
+
+use serde::de::value::MapAccessDeserializer;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Symbol, SymbolError};
+
+/// Max number of interned strings, and of interned symbols
+pub const MAX_INTERNED: usize = 1 << 16;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum InternError {
+    #[error("interner is full ({0} values)")]
+    Full(usize),
+}
+
+// values are looked up by key T, ids point to items E
+struct Interner<T: ?Sized + 'static, E: ?Sized + 'static = T> {
+    ids: HashMap<&'static T, u32>,
+    items: Vec<&'static E>,
+    max: usize,
+}
+
+impl<T: ?Sized + Eq + Hash + 'static, E: ?Sized + 'static> Interner<T, E> {
+    fn new(max: usize) -> Self {
+        Self {
+            ids: HashMap::new(),
+            items: Vec::new(),
+            max,
+        }
+    }
+}
+
+fn intern<T, E>(
+    lock: &RwLock<Interner<T, E>>,
+    v: &T,
+    leak: impl FnOnce(&T) -> Result<(&'static T, &'static E), InternError>,
+) -> Result<(u32, &'static E), InternError>
+where
+    T: ?Sized + Eq + Hash + 'static,
+    E: ?Sized + 'static,
+{
+    {
+        let interner = lock.read().unwrap();
+        if let Some(&id) = interner.ids.get(v) {
+            return Ok((id, interner.items[id as usize]));
+        }
+    }
+    let mut interner = lock.write().unwrap();
+    if let Some(&id) = interner.ids.get(v) {
+        return Ok((id, interner.items[id as usize]));
+    }
+    if interner.items.len() >= interner.max {
+        return Err(InternError::Full(interner.max));
+    }
+    let id = interner.items.len() as u32;
+    let (k, item) = leak(v)?;
+    interner.items.push(item);
+    interner.ids.insert(k, id);
+    Ok((id, item))
+}
+
+fn strs() -> &'static RwLock<Interner<str>> {
+    static STRS: OnceLock<RwLock<Interner<str>>> = OnceLock::new();
+    STRS.get_or_init(|| RwLock::new(Interner::new(MAX_INTERNED)))
+}
+
+fn symbols() -> &'static RwLock<Interner<Symbol, SymbolEntry>> {
+    static SYMBOLS: OnceLock<RwLock<Interner<Symbol, SymbolEntry>>> = OnceLock::new();
+    SYMBOLS.get_or_init(|| RwLock::new(Interner::new(MAX_INTERNED)))
+}
+
+/// Interned string (currency or exchange)
+#[derive(Clone, Copy)]
+pub struct IStr {
+    id: u32,
+    s: &'static str,
+}
+
+pub type CurrencyId = IStr;
+pub type ExchangeId = IStr;
+
+impl IStr {
+    /// Interned string, panics if the interner is full (see try_new)
+    pub fn new(s: &str) -> Self {
+        Self::try_new(s).expect("string interner is full")
+    }
+
+    pub fn try_new(s: &str) -> Result<Self, InternError> {
+        let (id, s) = intern(strs(), s, |s| {
+            let s: &'static str = Box::leak(Box::from(s));
+            Ok((s, s))
+        })?;
+        Ok(Self { id, s })
+    }
+
+    /// Interned currency, uppercased as in Symbol
+    pub fn currency(c: &str) -> Self {
+        Self::new(&c.to_uppercase())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.s
+    }
+}
+
+impl From<&str> for IStr {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<&String> for IStr {
+    fn from(s: &String) -> Self {
+        Self::new(s)
+    }
+}
+
+// by id, as the same strings have the same id
+impl PartialEq for IStr {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.id == rhs.id
+    }
+}
+
+impl Eq for IStr {}
+
+impl Hash for IStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+// by string, not by interning order
+impl Ord for IStr {
+    fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
+        self.s.cmp(rhs.s)
+    }
+}
+
+impl PartialOrd for IStr {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl fmt::Display for IStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.s)
+    }
+}
+
+impl fmt::Debug for IStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.s)
+    }
+}
+
+impl Serialize for IStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.s)
+    }
+}
+
+impl<'de> Deserialize<'de> for IStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::try_new(&s).map_err(de::Error::custom)
+    }
+}
+
+// interned symbol with its interned parts
+struct SymbolEntry {
+    symbol: Symbol,
+    text: String,
+    eg: ExchangeId,
+    bs: CurrencyId,
+    qt: CurrencyId,
+}
+
+/// Interned symbol
+#[derive(Clone, Copy)]
+pub struct SymbolId {
+    id: u32,
+    entry: &'static SymbolEntry,
+}
+
+impl SymbolId {
+    /// Interned symbol, panics if the interner is full (see try_new)
+    pub fn new(symbol: &Symbol) -> Self {
+        Self::try_new(symbol).expect("symbol interner is full")
+    }
+
+    pub fn try_new(symbol: &Symbol) -> Result<Self, InternError> {
+        let (id, entry) = intern(symbols(), symbol, |s| {
+            let entry: &'static SymbolEntry = Box::leak(Box::new(SymbolEntry {
+                symbol: s.clone(),
+                text: s.to_string(),
+                eg: IStr::try_new(&s.eg)?,
+                bs: IStr::try_new(&s.bs)?,
+                qt: IStr::try_new(&s.qt)?,
+            }));
+            Ok((&entry.symbol, entry))
+        })?;
+        Ok(Self { id, entry })
+    }
+
+    pub fn symbol(&self) -> &'static Symbol {
+        &self.entry.symbol
+    }
+
+    /// Display string "eg:BTC/USDT"
+    pub fn as_str(&self) -> &'static str {
+        &self.entry.text
+    }
+
+    pub fn eg(&self) -> ExchangeId {
+        self.entry.eg
+    }
+
+    pub fn bs(&self) -> CurrencyId {
+        self.entry.bs
+    }
+
+    pub fn qt(&self) -> CurrencyId {
+        self.entry.qt
+    }
+}
+
+impl From<&Symbol> for SymbolId {
+    fn from(symbol: &Symbol) -> Self {
+        Self::new(symbol)
+    }
+}
+
+impl From<SymbolId> for Symbol {
+    fn from(id: SymbolId) -> Self {
+        id.symbol().clone()
+    }
+}
+
+impl TryFrom<&str> for SymbolId {
+    type Error = SymbolError;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        Symbol::try_from(text).map(|s| Self::new(&s))
+    }
+}
+
+impl PartialEq for SymbolId {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.id == rhs.id
+    }
+}
+
+impl Eq for SymbolId {}
+
+impl Hash for SymbolId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl Ord for SymbolId {
+    fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
+        self.symbol().cmp(rhs.symbol())
+    }
+}
+
+impl PartialOrd for SymbolId {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl fmt::Display for SymbolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Debug for SymbolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.symbol())
+    }
+}
+
+impl Serialize for SymbolId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.symbol().serialize(serializer)
+    }
+}
+
+// Symbol struct as serialized, or its Display string "eg:BTC/USDT"
+struct SymbolVisitor;
+
+impl<'de> de::Visitor<'de> for SymbolVisitor {
+    type Value = Symbol;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a symbol struct or a string \"eg:BASE/QUOTE\"")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Symbol, E> {
+        Symbol::try_from(v).map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Symbol, A::Error> {
+        Symbol::deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+impl<'de> Deserialize<'de> for SymbolId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = if deserializer.is_human_readable() {
+            deserializer.deserialize_any(SymbolVisitor)?
+        } else {
+            Symbol::deserialize(deserializer)?
+        };
+        Self::try_new(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Depth, Depths};
+
+    #[test]
+    fn test_istr() {
+        let c1 = IStr::currency("btc");
+        let c2 = IStr::new("BTC");
+        assert_eq!(c1, c2);
+        assert_eq!(c1.to_string(), "BTC");
+        assert_eq!(json!(c1).to_string(), r#""BTC""#);
+        let c3: IStr = serde_json::from_str(r#""BTC""#).unwrap();
+        assert_eq!(c3, c1);
+    }
+
+    #[test]
+    fn test_symbol_id() {
+        let s = Symbol::try_from("eg:BTC/USDT").unwrap();
+        let id1 = SymbolId::new(&s);
+        let id2 = SymbolId::try_from("eg:btc/usdt").unwrap();
+        assert_eq!(id1, id2);
+        assert_eq!(id1.to_string(), "eg:BTC/USDT");
+        assert_eq!(id1.bs(), IStr::new("BTC"));
+        assert_eq!(id1.eg(), IStr::new("eg"));
+        assert_eq!(json!(id1), json!(s));
+        let id3: SymbolId = serde_json::from_value(json!(s)).unwrap();
+        assert_eq!(id3, id1);
+        let id4: SymbolId = serde_json::from_str(r#""eg:BTC/USDT""#).unwrap();
+        assert_eq!(id4, id1);
+        assert!(serde_json::from_str::<SymbolId>(r#""BTC""#).is_err());
+    }
+
+    #[test]
+    fn test_depths_by_symbol_id() {
+        let id = SymbolId::try_from("eg:ETH/USDT").unwrap();
+        let mut dd: Depths<SymbolId> = Depths::default();
+        dd.upsert(&id, Depth::new());
+        dd.update(&id, Depth::new());
+        assert!(dd.vecs.contains_key(&id));
+        let bytes = bincode::serialize(&dd).unwrap();
+        let dd: Depths<SymbolId> = bincode::deserialize(&bytes).unwrap();
+        assert!(dd.vecs.contains_key(&id));
+        assert!(dd.maps.contains_key(&id));
+    }
+
+    #[test]
+    fn test_interner_full() {
+        let lock: RwLock<Interner<str>> = RwLock::new(Interner::new(1));
+        let leak = |s: &str| {
+            let s: &'static str = Box::leak(Box::from(s));
+            Ok((s, s))
+        };
+        assert_eq!(intern(&lock, "BTC", leak).unwrap().1, "BTC");
+        assert_eq!(intern(&lock, "BTC", leak).unwrap().0, 0);
+        assert_eq!(intern(&lock, "ETH", leak), Err(InternError::Full(1)));
+    }
+}
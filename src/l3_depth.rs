@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{my_date_formatter, num_from_f64, Amount, Depth, DepthMap, Liq, Price, Side};
+
+/// Order-level liquidity (L3 entry) of market-by-order feeds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderLiq {
+    pub id: String,
+    pub side: Side,
+    pub p: Price,
+    pub a: Amount,
+    #[serde(with = "my_date_formatter")]
+    pub ts: DateTime<Utc>,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum L3Error {
+    #[error("duplicate order {0}")]
+    DuplicateOrder(String),
+    #[error("unknown order {0}")]
+    UnknownOrder(String),
+    #[error("executed amount {1:?} exceeds order {0} amount")]
+    ExecTooLarge(String, Amount),
+    #[error("order {0} amount {1:?} is not positive")]
+    NonPositiveAmount(String, Amount),
+}
+
+impl OrderLiq {
+    pub fn new(id: String, side: Side, p: Price, a: Amount, ts: DateTime<Utc>) -> Self {
+        Self { id, side, p, a, ts }
+    }
+
+    pub fn liq(&self) -> Liq {
+        Liq::from_pa(self.p, self.a)
+    }
+}
+
+/// L3 depth (market-by-order book): orders by id, aggregated into L2 Depth/DepthMap on demand
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct L3Depth {
+    pub orders: HashMap<String, OrderLiq>,
+}
+
+impl L3Depth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&OrderLiq> {
+        self.orders.get(id)
+    }
+
+    /// Adds the order, its amount must be positive
+    pub fn add(&mut self, order: OrderLiq) -> Result<(), L3Error> {
+        if order.a <= Amount::ZERO {
+            return Err(L3Error::NonPositiveAmount(order.id, order.a));
+        }
+        if self.orders.contains_key(&order.id) {
+            return Err(L3Error::DuplicateOrder(order.id));
+        }
+        self.orders.insert(order.id.clone(), order);
+        Ok(())
+    }
+
+    /// Changes price and amount of the order, zero amount deletes it
+    pub fn modify(
+        &mut self,
+        id: &str,
+        p: Price,
+        a: Amount,
+        ts: DateTime<Utc>,
+    ) -> Result<(), L3Error> {
+        if a < Amount::ZERO {
+            return Err(L3Error::NonPositiveAmount(id.to_string(), a));
+        }
+        if a == Amount::ZERO {
+            return self.delete(id).map(|_| ());
+        }
+        let order = self.get_mut(id)?;
+        order.p = p;
+        order.a = a;
+        order.ts = ts;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<OrderLiq, L3Error> {
+        self.orders
+            .remove(id)
+            .ok_or_else(|| L3Error::UnknownOrder(id.to_string()))
+    }
+
+    /// Executes the amount of the order, fully executed order is deleted
+    /// (the remaining amount within the tolerance is a full fill).
+    /// Returns the remaining amount
+    pub fn execute(&mut self, id: &str, a: Amount) -> Result<Amount, L3Error> {
+        if a <= Amount::ZERO {
+            return Err(L3Error::NonPositiveAmount(id.to_string(), a));
+        }
+        let order = self.get_mut(id)?;
+        let eps = Self::exec_tolerance(order.a);
+        if a > order.a + eps {
+            return Err(L3Error::ExecTooLarge(id.to_string(), a));
+        }
+        if a + eps >= order.a {
+            self.orders.remove(id);
+            return Ok(Amount::ZERO);
+        }
+        order.a -= a;
+        Ok(order.a)
+    }
+
+    /// Orders of the side at the price in queue (time) priority
+    pub fn level(&self, side: Side, p: Price) -> Vec<&OrderLiq> {
+        let mut oo: Vec<&OrderLiq> = self
+            .orders
+            .values()
+            .filter(|o| o.side == side && o.p == p)
+            .collect();
+        oo.sort_by_key(|o| o.ts);
+        oo
+    }
+
+    /// L2 view: amounts aggregated by price
+    pub fn to_depthmap(&self) -> DepthMap {
+        let mut m = DepthMap::new();
+        for o in self.orders.values() {
+            let levels = match o.side {
+                Side::Buy => &mut m.bids,
+                Side::Sell => &mut m.asks,
+            };
+            levels
+                .entry(o.p)
+                .and_modify(|l| *l = Liq::from_pa(o.p, l.amount() + o.a))
+                .or_insert_with(|| o.liq());
+        }
+        m
+    }
+
+    /// L2 view: sorted Depth
+    pub fn to_depth(&self) -> Depth {
+        self.to_depthmap().into_depth()
+    }
+
+    // private helpers
+
+    // amount tolerance of full fills for f64 drift: 1e-9 of the remaining amount
+    fn exec_tolerance(a: Amount) -> Amount {
+        a * num_from_f64(1e-9)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut OrderLiq, L3Error> {
+        self.orders
+            .get_mut(id)
+            .ok_or_else(|| L3Error::UnknownOrder(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::n;
+
+    fn order(id: &str, side: Side, p: f64, a: f64) -> OrderLiq {
+        OrderLiq::new(id.to_string(), side, Price(n(p)), Amount(n(a)), Utc::now())
+    }
+
+    fn l3() -> L3Depth {
+        let mut d = L3Depth::new();
+        d.add(order("1", Side::Buy, 10.0, 1.0)).unwrap();
+        d.add(order("2", Side::Buy, 10.0, 2.0)).unwrap();
+        d.add(order("3", Side::Buy, 9.0, 1.0)).unwrap();
+        d.add(order("4", Side::Sell, 11.0, 1.5)).unwrap();
+        d
+    }
+
+    #[test]
+    fn test_l3_to_depth() {
+        let d = l3().to_depth();
+        assert_eq!(
+            d.bids,
+            vec![
+                Liq::from_pa(Price(n(10.0)), Amount(n(3.0))),
+                Liq::from_pa(Price(n(9.0)), Amount(n(1.0)))
+            ]
+        );
+        assert_eq!(d.asks, vec![Liq::from_pa(Price(n(11.0)), Amount(n(1.5)))]);
+    }
+
+    #[test]
+    fn test_l3_ops() {
+        let mut d = l3();
+        assert_eq!(
+            d.add(order("1", Side::Buy, 1.0, 1.0)),
+            Err(L3Error::DuplicateOrder("1".to_string()))
+        );
+        assert_eq!(
+            d.add(order("5", Side::Buy, 1.0, 0.0)),
+            Err(L3Error::NonPositiveAmount("5".to_string(), Amount::ZERO))
+        );
+        assert!(matches!(
+            d.add(order("5", Side::Buy, 1.0, -1.0)),
+            Err(L3Error::NonPositiveAmount(..))
+        ));
+        assert_eq!(d.execute("2", Amount(n(0.5))), Ok(Amount(n(1.5))));
+        assert!(d.execute("2", Amount(n(2.0))).is_err());
+        assert_eq!(d.execute("1", Amount(n(1.0))), Ok(Amount::ZERO));
+        assert!(d.get("1").is_none());
+        d.modify("3", Price(n(10.0)), Amount(n(1.0)), Utc::now())
+            .unwrap();
+        assert_eq!(d.level(Side::Buy, Price(n(10.0))).len(), 2);
+        d.modify("4", Price(n(11.0)), Amount::ZERO, Utc::now())
+            .unwrap();
+        assert!(d.to_depth().asks.is_empty());
+        assert_eq!(d.delete("4"), Err(L3Error::UnknownOrder("4".to_string())));
+        assert!(matches!(
+            d.modify("3", Price(n(10.0)), Amount(n(-1.0)), Utc::now()),
+            Err(L3Error::NonPositiveAmount(..))
+        ));
+        assert!(matches!(
+            d.execute("3", Amount(n(-5.0))),
+            Err(L3Error::NonPositiveAmount(..))
+        ));
+        assert!(matches!(
+            d.execute("3", Amount::ZERO),
+            Err(L3Error::NonPositiveAmount(..))
+        ));
+        assert_eq!(d.get("3").unwrap().a, Amount(n(1.0)));
+    }
+
+    #[test]
+    fn test_l3_execute_full_fill() {
+        let mut d = L3Depth::new();
+        d.add(order("1", Side::Sell, 10.0, 0.3)).unwrap();
+        let rest = d.execute("1", Amount(n(0.1))).unwrap();
+        assert!(rest > Amount::ZERO);
+        assert_eq!(d.execute("1", Amount(n(0.2))), Ok(Amount::ZERO));
+        assert!(d.get("1").is_none());
+    }
+}
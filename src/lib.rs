@@ -1,32 +1,65 @@
 mod amount;
+mod currency_aliases;
 mod depth;
+mod depth_checksum;
+mod depth_sync;
 mod depthmap;
 mod depths;
+mod fee;
+mod instrument;
+mod instrument_spec;
+mod intern;
+mod l3_depth;
 mod liq;
+mod money;
+mod num;
+mod order_intent;
 mod order_placed;
 mod order_to_place;
+mod portfolio;
 mod price;
+mod rates;
+mod ratio;
 mod side;
+mod sorted_depth;
 mod spread;
 mod symbol;
+mod symbol_format;
 mod trade;
 mod worth;
 
 pub mod depth_util;
 pub mod my_date_formatter;
 pub mod my_duration_formatter;
+pub mod num_str;
 
 pub use amount::*;
+pub use currency_aliases::*;
 pub use depth::*;
+pub use depth_checksum::*;
+pub use depth_sync::*;
 pub use depthmap::*;
 pub use depths::*;
+pub use fee::*;
+pub use instrument::*;
+pub use instrument_spec::*;
+pub use intern::*;
+pub use l3_depth::*;
 pub use liq::*;
+pub use money::*;
+pub use num::*;
+pub use order_intent::*;
 pub use order_placed::*;
 pub use order_to_place::*;
+pub use portfolio::*;
 pub use price::*;
+pub use rates::*;
+pub use ratio::*;
 pub use side::*;
+pub use sorted_depth::*;
 pub use spread::*;
 pub use symbol::*;
+pub use symbol_format::*;
 pub use trade::*;
 pub use worth::*;
 
@@ -37,4 +70,6 @@ pub type Currency = String;
 pub type Exchange = String;
 
 pub type LiqByCurrency = HashMap<Currency, Liq>;
+// see Portfolio for free/locked funds by exchange
 pub type Balances = HashMap<Currency, Amount>;
+pub type InstrumentSpecs = HashMap<Symbol, InstrumentSpec>;
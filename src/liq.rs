@@ -1,9 +1,11 @@
-use super::{Amount, Price, Worth};
-use serde::{Deserialize, Serialize};
+use super::{num_from_f64, num_from_str, Amount, Num, NumError, Price, Ratio, Worth, NUM_ONE};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Liq (liquidity) is a complex data type that contains all necessary origin information,
-/// suitable for further processing. Thus, depth (order book) is a vec of Liqs
+/// suitable for further processing. Thus, depth (order book) is a vec of Liqs.
+/// On deserialize w is checked against p*a (see Liq::worth_tolerance) or computed if absent
 #[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[serde(try_from = "LiqRaw")]
 pub struct Liq {
     pub(super) p: Price,
     pub(super) a: Amount,
@@ -27,6 +29,31 @@ impl Liq {
         }
     }
 
+    /// Checked constructor: w must be equal to p*a within the relative tolerance
+    pub fn checked(p: Price, a: Amount, w: Worth, tol: Ratio) -> Result<Self, LiqError> {
+        let liq = Self { p, a, w };
+        if !liq.is_consistent(tol) {
+            return Err(LiqError::InconsistentWorth(w, Worth::from_pa(p, a)));
+        }
+        Ok(liq)
+    }
+
+    /// Default relative tolerance of the worth check on deserialize
+    pub fn worth_tolerance() -> Ratio {
+        Ratio::from_fraction(num_from_f64(1e-9))
+    }
+
+    pub fn is_consistent(&self, tol: Ratio) -> bool {
+        let expected = Worth::from_pa(self.p, self.a);
+        let (lo, hi) = (self.w.min(expected), self.w.max(expected));
+        hi.0 - lo.0 <= tol.0 * hi.0
+    }
+
+    /// Recomputes derived w from p and a
+    pub fn normalize(&mut self) {
+        self.w = Worth::from_pa(self.p, self.a);
+    }
+
     pub fn price(&self) -> Price {
         self.p
     }
@@ -38,8 +65,106 @@ impl Liq {
     pub fn worth(&self) -> Worth {
         self.w
     }
+
+    /// Liq of the inverse symbol (quote/base): price 1/p, amount and worth swapped.
+    /// None for zero price
+    pub fn invert(&self) -> Option<Self> {
+        if self.p == Price::ZERO {
+            return None;
+        }
+        Some(Self {
+            p: Price(NUM_ONE / self.p.0),
+            a: Amount(self.w.0),
+            w: Worth(self.a.0),
+        })
+    }
+
+    /// Fallible parsing of exchange [price, amount, ..] arrays of strings.
+    /// Not a TryFrom: it would conflict with the panicking From impls
+    pub fn try_from_strs<S: AsRef<str>>(pa: &[S]) -> Result<Self, LiqError> {
+        let [p, a, ..] = pa else {
+            return Err(LiqError::TooShort(pa.len()));
+        };
+        let p = Price::new(parse_num(p.as_ref())?)?;
+        let a = Amount::new(parse_num(a.as_ref())?)?;
+        Ok(Self::from_pa(p, a))
+    }
+
+    /// Fallible conversion of exchange [price, amount, ..] arrays of numbers
+    pub fn try_from_f64s(pa: &[f64]) -> Result<Self, LiqError> {
+        let [p, a, ..] = pa else {
+            return Err(LiqError::TooShort(pa.len()));
+        };
+        let p = Price::try_from(*p)?;
+        let a = Amount::try_from(*a)?;
+        Ok(Self::from_pa(p, a))
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum LiqError {
+    #[error("too short liq array of len {0}")]
+    TooShort(usize),
+    #[error("bad number {0}")]
+    BadNumber(String),
+    #[error("bad liq: {0}")]
+    Num(#[from] NumError),
+    #[error("worth {0:?} is not price*amount {1:?}")]
+    InconsistentWorth(Worth, Worth),
+}
+
+// Liq as it comes on deserialize, w is optional
+#[derive(Deserialize)]
+struct LiqRaw {
+    p: Price,
+    a: Amount,
+    // serialized as plain Worth, so bincode reads it without Option tag
+    #[serde(default, deserialize_with = "deserialize_some_worth")]
+    w: Option<Worth>,
+}
+
+fn deserialize_some_worth<'de, D>(deserializer: D) -> Result<Option<Worth>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Worth::deserialize(deserializer).map(Some)
+}
+
+impl TryFrom<LiqRaw> for Liq {
+    type Error = LiqError;
+
+    fn try_from(raw: LiqRaw) -> Result<Self, Self::Error> {
+        match raw.w {
+            Some(w) => Self::checked(raw.p, raw.a, w, Self::worth_tolerance()),
+            None => Ok(Self::from_pa(raw.p, raw.a)),
+        }
+    }
+}
+
+/// Deserializes Liq recomputing w instead of checking it,
+/// use as #[serde(deserialize_with = "deserialize_liq_normalized")]
+pub fn deserialize_liq_normalized<'de, D>(deserializer: D) -> Result<Liq, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = LiqRaw::deserialize(deserializer)?;
+    Ok(Liq::from_pa(raw.p, raw.a))
+}
+
+/// Deserializes Vec<Liq> (depth side) recomputing w instead of checking it
+pub fn deserialize_liqs_normalized<'de, D>(deserializer: D) -> Result<Vec<Liq>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raws = Vec::<LiqRaw>::deserialize(deserializer)?;
+    Ok(raws.into_iter().map(|r| Liq::from_pa(r.p, r.a)).collect())
+}
+
+fn parse_num(s: &str) -> Result<Num, LiqError> {
+    num_from_str(s).map_err(|_| LiqError::BadNumber(s.to_string()))
 }
 
+// unchecked, see Liq::checked
 impl From<(Price, Amount, Worth)> for Liq {
     fn from((p, a, w): (Price, Amount, Worth)) -> Self {
         Self { p, a, w }
@@ -61,8 +186,8 @@ impl From<(Price, Worth)> for Liq {
 impl From<&[String]> for Liq {
     fn from(pa: &[String]) -> Self {
         // must panic on bad incoming data, because this must be fixed on dev stage
-        let p = Price(pa[0].parse().unwrap());
-        let a = Amount(pa[1].parse().unwrap());
+        let p = Price::new(pa[0].parse().unwrap()).unwrap();
+        let a = Amount::new(pa[1].parse().unwrap()).unwrap();
         Self::from((p, a))
     }
 }
@@ -70,16 +195,17 @@ impl From<&[String]> for Liq {
 impl From<&Vec<String>> for Liq {
     fn from(pa: &Vec<String>) -> Self {
         // must panic on bad incoming data, because this must be fixed on dev stage
-        let p = Price(pa[0].parse().unwrap());
-        let a = Amount(pa[1].parse().unwrap());
+        let p = Price::new(pa[0].parse().unwrap()).unwrap();
+        let a = Amount::new(pa[1].parse().unwrap()).unwrap();
         Self::from((p, a))
     }
 }
 
 impl From<&[f64]> for Liq {
     fn from(pa: &[f64]) -> Self {
-        let p = Price(pa[0]);
-        let a = Amount(pa[1]);
+        // must panic on bad incoming data, because this must be fixed on dev stage
+        let p = Price::try_from(pa[0]).unwrap();
+        let a = Amount::try_from(pa[1]).unwrap();
         Self::from((p, a))
     }
 }
@@ -89,41 +215,151 @@ mod tests {
     use serde_json::json;
 
     use super::*;
+    use crate::n;
 
     #[test]
     fn test_liq() {
-        let p = Price(10.0);
-        let a = Amount(0.5);
+        let p = Price(n(10.0));
+        let a = Amount(n(0.5));
         let liq = Liq::from_pa(p, a);
-        assert_eq!(liq.worth(), Worth(5.0));
+        assert_eq!(liq.worth(), Worth(n(5.0)));
     }
 
     #[test]
     fn test_liq2() {
-        let p = Price(10.0);
-        let a = Amount(0.5);
+        let p = Price(n(10.0));
+        let a = Amount(n(0.5));
         let liq = Liq::from((p, a));
-        assert_eq!(liq.worth(), Worth(5.0));
+        assert_eq!(liq.worth(), Worth(n(5.0)));
     }
 
     #[test]
     fn test_show_liq() {
-        let liq = Liq::from_pa(Price(10.0), Amount(0.5));
+        let liq = Liq::from_pa(Price(n(10.0)), Amount(n(0.5)));
         dbg!(liq);
-        assert!(true);
+        assert!(format!("{:?}", liq).starts_with("Liq"));
+    }
+
+    #[test]
+    fn test_liq_invert() {
+        let liq = Liq::from_pa(Price(n(4.0)), Amount(n(2.0)))
+            .invert()
+            .unwrap();
+        assert_eq!(liq.price(), Price(n(0.25)));
+        assert_eq!(liq.amount(), Amount(n(8.0)));
+        assert_eq!(liq.worth(), Worth(n(2.0)));
+        assert!(Liq::from_pa(Price(n(0.0)), Amount(n(2.0)))
+            .invert()
+            .is_none());
     }
 
     #[test]
     fn test_json_to_liq() {
         let r: Liq = serde_json::from_str(r#"{"p":1,"a":2,"w":2}"#).unwrap();
-        assert_eq!(r.worth(), Worth(2.0));
+        assert_eq!(r.worth(), Worth(n(2.0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_liq_from_nan_strings() {
+        let pa = vec!["NaN".to_string(), "1.0".to_string()];
+        let _ = Liq::from(&pa);
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_liq_try_from_f64s_out_of_range() {
+        assert_eq!(
+            Liq::try_from_f64s(&[1e30, 1.0]),
+            Err(LiqError::Num(NumError::OutOfRange(1e30)))
+        );
+    }
+
+    #[test]
+    fn test_liq_try_from_strs() {
+        let liq = Liq::try_from_strs(&["10", "0.5", "3"]).unwrap();
+        assert_eq!(liq.worth(), Worth(n(5.0)));
+        assert_eq!(Liq::try_from_strs(&["10"]), Err(LiqError::TooShort(1)));
+        assert_eq!(
+            Liq::try_from_strs(&["10", "x"]),
+            Err(LiqError::BadNumber("x".to_string()))
+        );
+        #[cfg(not(feature = "decimal"))]
+        assert!(matches!(
+            Liq::try_from_strs(&["NaN".to_string(), "1".to_string()]),
+            Err(LiqError::Num(NumError::NotFinite(_)))
+        ));
+        #[cfg(feature = "decimal")]
+        assert!(matches!(
+            Liq::try_from_strs(&["NaN".to_string(), "1".to_string()]),
+            Err(LiqError::BadNumber(_))
+        ));
+        assert!(matches!(
+            Liq::try_from_f64s(&[1.0, -1.0]),
+            Err(LiqError::Num(NumError::Negative(_)))
+        ));
+    }
+
+    #[test]
+    fn test_liq_checked() {
+        let tol = Ratio::from_percent(n(1.0));
+        let liq = Liq::checked(Price(n(10.0)), Amount(n(0.5)), Worth(n(5.04)), tol).unwrap();
+        assert_eq!(liq.worth(), Worth(n(5.04)));
+        assert_eq!(
+            Liq::checked(Price(n(10.0)), Amount(n(0.5)), Worth(n(6.0)), tol),
+            Err(LiqError::InconsistentWorth(Worth(n(6.0)), Worth(n(5.0))))
+        );
+        let mut liq = Liq::from((Price(n(10.0)), Amount(n(0.5)), Worth(n(6.0))));
+        liq.normalize();
+        assert_eq!(liq.worth(), Worth(n(5.0)));
+    }
+
+    #[test]
+    fn test_json_to_liq_worth() {
+        let r: Result<Liq, _> = serde_json::from_str(r#"{"p":1,"a":2,"w":3}"#);
+        assert!(r.is_err());
+        let r: Liq = serde_json::from_str(r#"{"p":"2","a":2}"#).unwrap();
+        assert_eq!(r.worth(), Worth(n(4.0)));
+
+        #[derive(Deserialize)]
+        struct Side {
+            #[serde(deserialize_with = "deserialize_liqs_normalized")]
+            liqs: Vec<Liq>,
+        }
+        let s: Side = serde_json::from_str(r#"{"liqs":[{"p":1,"a":2,"w":3}]}"#).unwrap();
+        assert_eq!(s.liqs[0].worth(), Worth(n(2.0)));
+    }
+
+    #[test]
+    fn test_liq_bincode() {
+        let liq = Liq::from_pa(Price(n(10.0)), Amount(n(0.5)));
+        let bytes = bincode::serialize(&liq).unwrap();
+        let liq2: Liq = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(liq2, liq);
+    }
+
+    #[test]
+    fn test_json_to_liq_negative() {
+        let r: Result<Liq, _> = serde_json::from_str(r#"{"p":1,"a":-2,"w":-2}"#);
+        assert!(r.is_err());
     }
 
     #[test]
+    #[cfg(not(feature = "decimal"))]
     fn test_liq_to_json() {
         // https://serde.rs/derive.html
-        let r = Liq::from_pa(Price(1.0), Amount(10.0));
+        let r = Liq::from_pa(Price(n(1.0)), Amount(n(10.0)));
         let v = json!(r);
         assert_eq!(v.to_string(), r#"{"a":10.0,"p":1.0,"w":10.0}"#);
     }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_liq_to_json_decimal() {
+        let r = Liq::from_pa(Price(n(1.0)), Amount(n(10.0)));
+        let v = json!(r);
+        assert_eq!(v.to_string(), r#"{"a":"10","p":"1","w":"10"}"#);
+        let r2: Liq = serde_json::from_str(&v.to_string()).unwrap();
+        assert_eq!(r2, r);
+    }
 }
@@ -0,0 +1,196 @@
+use core::ops;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, Balances, Currency, Liq, LiqByCurrency, Num, Symbol, Worth, NUM_ZERO};
+
+/// Money is a value tagged with its currency.
+/// Opt-in layer over Amount (base currency) and Worth (quote currency)
+/// to catch mixing of different currencies: arithmetic is checked in runtime.
+/// Value can be negative (e.g. PnL)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "MoneyRaw")]
+pub struct Money {
+    pub c: Currency,
+    pub v: Num,
+}
+
+// Money as it comes on deserialize, currency is not uppercased
+#[derive(Deserialize)]
+struct MoneyRaw {
+    c: Currency,
+    v: Num,
+}
+
+impl From<MoneyRaw> for Money {
+    fn from(raw: MoneyRaw) -> Self {
+        Self::new(raw.c, raw.v)
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum MoneyError {
+    #[error("currency mismatch {0} vs {1}")]
+    CurrencyMismatch(Currency, Currency),
+}
+
+impl Money {
+    pub fn new(c: Currency, v: Num) -> Self {
+        Self {
+            c: c.to_uppercase(),
+            v,
+        }
+    }
+
+    pub fn zero(c: Currency) -> Self {
+        Self::new(c, NUM_ZERO)
+    }
+
+    /// Amount in base currency of the symbol
+    pub fn base(symbol: &Symbol, a: Amount) -> Self {
+        Self::new(symbol.bs.clone(), a.0)
+    }
+
+    /// Worth in quote currency of the symbol
+    pub fn quote(symbol: &Symbol, w: Worth) -> Self {
+        Self::new(symbol.qt.clone(), w.0)
+    }
+
+    /// Amount of the currency on the balances, zero if there is no such currency
+    pub fn from_balances(balances: &Balances, c: &Currency) -> Self {
+        let c = c.to_uppercase();
+        let v = balances.get(&c).map_or(NUM_ZERO, |a| a.0);
+        Self::new(c, v)
+    }
+
+    /// Amounts of liqs by their currency
+    pub fn from_liq_by_currency(liqs: &LiqByCurrency) -> Vec<Self> {
+        liqs.iter()
+            .map(|(c, l)| Self::new(c.clone(), l.amount().0))
+            .collect()
+    }
+
+    /// Adds the money to the balance of its currency
+    pub fn add_to(&self, balances: &mut Balances) {
+        balances.entry(self.c.clone()).or_insert(Amount::ZERO).0 += self.v;
+    }
+
+    pub fn amount(&self) -> Amount {
+        Amount(self.v)
+    }
+
+    pub fn worth(&self) -> Worth {
+        Worth(self.v)
+    }
+
+    pub fn checked_add(&self, rhs: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(rhs)?;
+        Ok(Self::new(self.c.clone(), self.v + rhs.v))
+    }
+
+    pub fn checked_sub(&self, rhs: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(rhs)?;
+        Ok(Self::new(self.c.clone(), self.v - rhs.v))
+    }
+
+    // private helpers
+
+    fn check_currency(&self, rhs: &Money) -> Result<(), MoneyError> {
+        if self.c != rhs.c {
+            return Err(MoneyError::CurrencyMismatch(self.c.clone(), rhs.c.clone()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.v, self.c)
+    }
+}
+
+impl ops::Mul<Num> for Money {
+    type Output = Self;
+    fn mul(self, rhs: Num) -> Self {
+        Self::new(self.c, self.v * rhs)
+    }
+}
+
+impl Liq {
+    /// Amount of the liq in base currency of the symbol
+    pub fn base_money(&self, symbol: &Symbol) -> Money {
+        Money::base(symbol, self.amount())
+    }
+
+    /// Worth of the liq in quote currency of the symbol
+    pub fn quote_money(&self, symbol: &Symbol) -> Money {
+        Money::quote(symbol, self.worth())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{btcusdt, n, Price};
+
+    #[test]
+    fn test_money_from_json_lowercase() {
+        let m: Money = serde_json::from_value(json!({"c": "btc", "v": n(1.5)})).unwrap();
+        assert_eq!(m, Money::new("BTC".into(), n(1.5)));
+        let sum = m.checked_add(&Money::new("btc".into(), n(1.0))).unwrap();
+        assert_eq!(sum, Money::new("BTC".into(), n(2.5)));
+        let m2: Money = serde_json::from_value(json!(m)).unwrap();
+        assert_eq!(m2, m);
+    }
+
+    #[test]
+    fn test_money_add() {
+        let m1 = Money::new("usdt".into(), n(1.0));
+        let m2 = Money::new("USDT".into(), n(2.0));
+        assert_eq!(
+            m1.checked_add(&m2).unwrap(),
+            Money::new("USDT".into(), n(3.0))
+        );
+    }
+
+    #[test]
+    fn test_money_currency_mismatch() {
+        let liq = Liq::from_pa(Price(n(10.0)), Amount(n(2.0)));
+        let w = liq.quote_money(&btcusdt());
+        let a = liq.base_money(&btcusdt());
+        assert_eq!(
+            w.checked_sub(&a),
+            Err(MoneyError::CurrencyMismatch("USDT".into(), "BTC".into()))
+        );
+    }
+
+    #[test]
+    fn test_money_balances() {
+        let mut b = Balances::new();
+        Money::new("btc".into(), n(1.0)).add_to(&mut b);
+        Money::new("btc".into(), n(0.5)).add_to(&mut b);
+        assert_eq!(b.get("BTC"), Some(&Amount(n(1.5))));
+        assert_eq!(
+            Money::from_balances(&b, &"eth".into()),
+            Money::zero("ETH".into())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "decimal"))]
+    fn test_money_to_json() {
+        let m = Money::new("btc".into(), n(1.5));
+        assert_eq!(json!(m).to_string(), r#"{"c":"BTC","v":1.5}"#);
+        assert_eq!(m.to_string(), "1.5 BTC");
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_money_to_json_decimal() {
+        let m = Money::new("btc".into(), n(1.5));
+        assert_eq!(json!(m).to_string(), r#"{"c":"BTC","v":"1.5"}"#);
+    }
+}
@@ -0,0 +1,288 @@
+//! Numeric backend of Price, Amount and Worth.
+//!
+//! By default it's plain f64 (fast, but sums and products drift).
+//! With `decimal` feature it's rust_decimal::Decimal, so sums of amounts
+//! and price * amount products stay exact and equality checks are reliable.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer};
+
+#[cfg(feature = "decimal")]
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+
+#[cfg(not(feature = "decimal"))]
+pub type Num = f64;
+
+#[cfg(feature = "decimal")]
+pub type Num = rust_decimal::Decimal;
+
+#[cfg(not(feature = "decimal"))]
+pub const NUM_ZERO: Num = 0.0;
+
+#[cfg(feature = "decimal")]
+pub const NUM_ZERO: Num = rust_decimal::Decimal::ZERO;
+
+#[cfg(not(feature = "decimal"))]
+pub const NUM_ONE: Num = 1.0;
+
+#[cfg(feature = "decimal")]
+pub const NUM_ONE: Num = rust_decimal::Decimal::ONE;
+
+/// Converts f64 into the backend number, unchecked (for constants).
+/// With `decimal` backend non-finite and out of range values become zero,
+/// use num_try_from_f64 or check_f64 for input
+#[cfg(not(feature = "decimal"))]
+pub fn num_from_f64(v: f64) -> Num {
+    v
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_from_f64(v: f64) -> Num {
+    Num::from_f64(v).unwrap_or_default()
+}
+
+/// Converts f64 into the backend number: NaN, inf and values out of
+/// the backend range (~7.9e28 for `decimal`) are not allowed
+#[cfg(not(feature = "decimal"))]
+pub fn num_try_from_f64(v: f64) -> Result<Num, NumError> {
+    if !v.is_finite() {
+        return Err(NumError::NotFinite(v));
+    }
+    Ok(v)
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_try_from_f64(v: f64) -> Result<Num, NumError> {
+    if !v.is_finite() {
+        return Err(NumError::NotFinite(v));
+    }
+    Num::from_f64(v).ok_or(NumError::OutOfRange(v))
+}
+
+/// Backend number of f64 literal in tests, exact for `decimal` backend: n(0.1)
+#[cfg(all(test, not(feature = "decimal")))]
+pub(crate) fn n(v: f64) -> Num {
+    v
+}
+
+#[cfg(all(test, feature = "decimal"))]
+pub(crate) fn n(v: f64) -> Num {
+    num_from_str(&v.to_string()).unwrap()
+}
+
+/// Liq of f64 price and amount in tests
+#[cfg(test)]
+pub(crate) fn liq(p: f64, a: f64) -> crate::Liq {
+    crate::Liq::from_pa(crate::Price(n(p)), crate::Amount(n(a)))
+}
+
+/// eg:BTC/USDT symbol in tests
+#[cfg(test)]
+pub(crate) fn btcusdt() -> crate::Symbol {
+    crate::Symbol::new("eg".into(), "btc".into(), "usdt".into())
+}
+
+/// Converts the backend number into f64 (lossy for `decimal` backend)
+#[cfg(not(feature = "decimal"))]
+pub fn num_to_f64(v: Num) -> f64 {
+    v
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_to_f64(v: Num) -> f64 {
+    v.to_f64().unwrap_or_default()
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
+pub enum NumError {
+    #[error("not finite number {0}")]
+    NotFinite(f64),
+    #[error("negative number {0}")]
+    Negative(Num),
+    #[error("number {0} is out of range")]
+    OutOfRange(f64),
+}
+
+/// Checks that the number is finite and not negative,
+/// as required for Price, Amount and Worth
+pub fn check_num(v: Num) -> Result<Num, NumError> {
+    #[cfg(not(feature = "decimal"))]
+    if !v.is_finite() {
+        return Err(NumError::NotFinite(v));
+    }
+    if v < NUM_ZERO {
+        return Err(NumError::Negative(v));
+    }
+    Ok(v)
+}
+
+/// Checked conversion from f64: NaN, inf, out of range and negative values are not allowed
+pub fn check_f64(v: f64) -> Result<Num, NumError> {
+    check_num(num_try_from_f64(v)?)
+}
+
+/// Total order of the backend numbers.
+/// For f64 it's `total_cmp` except 0.0 == -0.0 (to agree with `==`),
+/// NaN is excluded by checked constructors
+#[cfg(not(feature = "decimal"))]
+pub fn num_cmp(a: Num, b: Num) -> Ordering {
+    if a == b {
+        Ordering::Equal
+    } else {
+        a.total_cmp(&b)
+    }
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_cmp(a: Num, b: Num) -> Ordering {
+    a.cmp(&b)
+}
+
+// tolerance of f64 rounding to steps (tick size, lot step)
+#[cfg(not(feature = "decimal"))]
+const STEP_EPS: f64 = 1e-9;
+
+// relative tolerance for many steps: f64 error of v / step grows with the step count
+#[cfg(not(feature = "decimal"))]
+const STEP_REL_EPS: f64 = 1e-12;
+
+/// Number of whole steps in v, rounded by `round` (floor or ceil).
+/// For f64 values within STEP_EPS (relative for many steps) of a whole step snap to it
+#[cfg(not(feature = "decimal"))]
+fn steps(v: Num, step: Num, round: fn(Num) -> Num) -> Num {
+    let n = v / step;
+    let eps = (STEP_REL_EPS * n.abs()).max(STEP_EPS);
+    if (n - n.round()).abs() < eps {
+        n.round()
+    } else {
+        round(n)
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn steps(v: Num, step: Num, round: fn(Num) -> Num) -> Num {
+    round(v / step)
+}
+
+// n * step, but for f64 divides by 1/step when it's whole (0.1, 0.01...),
+// so 271231 steps of 0.1 are 27123.1, not 27123.100000000002
+#[cfg(not(feature = "decimal"))]
+fn mul_step(n: Num, step: Num) -> Num {
+    let inv = (1.0 / step).round();
+    if inv >= 1.0 && (inv * step - 1.0).abs() < STEP_EPS {
+        n / inv
+    } else {
+        n * step
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn mul_step(n: Num, step: Num) -> Num {
+    n * step
+}
+
+/// Rounds v down to a multiple of step, zero step means no rounding
+pub fn num_floor_to_step(v: Num, step: Num) -> Num {
+    if step <= NUM_ZERO {
+        return v;
+    }
+    mul_step(steps(v, step, |n: Num| n.floor()), step)
+}
+
+/// Rounds v up to a multiple of step, zero step means no rounding
+pub fn num_ceil_to_step(v: Num, step: Num) -> Num {
+    if step <= NUM_ZERO {
+        return v;
+    }
+    mul_step(steps(v, step, |n: Num| n.ceil()), step)
+}
+
+/// Checks v is a multiple of step, zero step means any v
+pub fn num_is_on_step(v: Num, step: Num) -> bool {
+    step <= NUM_ZERO || steps(v, step, |n: Num| n.floor()) == steps(v, step, |n: Num| n.ceil())
+}
+
+/// Decimal places of the step: 0.01 -> 2, 1 -> 0
+#[cfg(not(feature = "decimal"))]
+pub fn num_precision(step: Num) -> usize {
+    (0..18)
+        .find(|&k| num_is_on_step(step * 10f64.powi(k as i32), 1.0))
+        .unwrap_or(18)
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_precision(step: Num) -> usize {
+    step.normalize().scale() as usize
+}
+
+/// Formats v with exactly `prec` decimal places
+pub fn num_to_string_prec(v: Num, prec: usize) -> String {
+    format!("{:.*}", prec, v)
+}
+
+/// Parses the backend number from a string: "27123.10", "1e-8"
+#[cfg(not(feature = "decimal"))]
+pub fn num_from_str(s: &str) -> Result<Num, std::num::ParseFloatError> {
+    Num::from_str(s.trim())
+}
+
+#[cfg(feature = "decimal")]
+pub fn num_from_str(s: &str) -> Result<Num, rust_decimal::Error> {
+    let s = s.trim();
+    Num::from_str(s).or_else(|_| Num::from_scientific(s))
+}
+
+// Exchanges send numbers either as JSON strings or as JSON numbers
+struct NumVisitor;
+
+impl de::Visitor<'_> for NumVisitor {
+    type Value = Num;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number or a string with a number")
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Num, E> {
+        num_try_from_f64(v).map_err(de::Error::custom)
+    }
+
+    #[cfg(not(feature = "decimal"))]
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Num, E> {
+        Ok(v as Num)
+    }
+
+    #[cfg(feature = "decimal")]
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Num, E> {
+        Ok(Num::from(v))
+    }
+
+    #[cfg(not(feature = "decimal"))]
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Num, E> {
+        Ok(v as Num)
+    }
+
+    #[cfg(feature = "decimal")]
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Num, E> {
+        Ok(Num::from(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Num, E> {
+        num_from_str(v).map_err(de::Error::custom)
+    }
+}
+
+/// Deserializes the backend number from either a string or a number.
+/// Non-self-describing formats (bincode) use the backend number format as is
+pub fn deserialize_num<'de, D>(deserializer: D) -> Result<Num, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(NumVisitor)
+    } else {
+        <Num as Deserialize>::deserialize(deserializer)
+    }
+}
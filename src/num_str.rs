@@ -0,0 +1,80 @@
+//! Serializes Price, Amount or Worth as a JSON string, as exchanges do: "27123.1".
+//! Deserializes from either a string or a number.
+//!
+//! Usage: #[serde(with = "trading_types::num_str")].
+//! With the instrument precision (decimal places), so f64 noise doesn't go out:
+//! #[serde(serialize_with = "trading_types::num_str::serialize_prec::<2, _, _>",
+//! deserialize_with = "trading_types::num_str::deserialize")]
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::{num_to_string_prec, Num};
+
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<Num>,
+    S: Serializer,
+{
+    let v: Num = (*v).into();
+    serializer.serialize_str(&v.to_string())
+}
+
+/// Serializes with exactly PREC decimal places: Price(0.1) + Price(0.2) -> "0.30"
+pub fn serialize_prec<const PREC: usize, T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<Num>,
+    S: Serializer,
+{
+    let v: Num = (*v).into();
+    serializer.serialize_str(&num_to_string_prec(v, PREC))
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::{n, Amount, Price};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Level {
+        #[serde(with = "crate::num_str")]
+        p: Price,
+        #[serde(with = "crate::num_str")]
+        a: Amount,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct PrecLevel {
+        #[serde(
+            serialize_with = "crate::num_str::serialize_prec::<2, _, _>",
+            deserialize_with = "crate::num_str::deserialize"
+        )]
+        p: Price,
+    }
+
+    #[test]
+    fn test_num_str_round_trip() {
+        let l: Level = serde_json::from_str(r#"{"p":"27123.1","a":0.5}"#).unwrap();
+        assert_eq!(l.p, Price(n(27123.1)));
+        assert_eq!(json!(l).to_string(), r#"{"a":"0.5","p":"27123.1"}"#);
+    }
+
+    #[test]
+    fn test_num_str_prec() {
+        let l = PrecLevel {
+            p: Price(n(0.1)) + Price(n(0.2)),
+        };
+        assert_eq!(json!(l).to_string(), r#"{"p":"0.30"}"#);
+        let l2: PrecLevel = serde_json::from_str(r#"{"p":"0.30"}"#).unwrap();
+        assert_eq!(l2.p, Price(n(0.3)));
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Side;
+
+/// Position side in hedge mode
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PositionSide {
+    #[serde(rename(serialize = "long", deserialize = "long"))]
+    #[serde(alias = "Long", alias = "LONG")]
+    Long,
+    #[serde(rename(serialize = "short", deserialize = "short"))]
+    #[serde(alias = "Short", alias = "SHORT")]
+    Short,
+}
+
+/// Intent of the order on the position
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PositionEffect {
+    #[serde(rename(serialize = "open", deserialize = "open"))]
+    #[serde(alias = "Open", alias = "OPEN")]
+    Open,
+    #[serde(rename(serialize = "close", deserialize = "close"))]
+    #[serde(alias = "Close", alias = "CLOSE")]
+    Close,
+}
+
+/// Derivative order intent: position side (hedge mode), open/close and reduce-only.
+/// Empty intent is a usual (spot or one-way mode) order
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct OrderIntent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_side: Option<PositionSide>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<PositionEffect>,
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum OrderIntentError {
+    #[error("bad position side {0}")]
+    BadPositionSide(String),
+    #[error("bad position effect {0}")]
+    BadPositionEffect(String),
+    #[error("reduce-only order can't open a position")]
+    ReduceOnlyOpen,
+    #[error("reduce-only is not allowed in hedge mode, use close")]
+    ReduceOnlyInHedgeMode,
+    #[error("{0} order can't {1:?} {2:?} position")]
+    SideMismatch(Side, PositionEffect, PositionSide),
+}
+
+impl fmt::Display for PositionSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl PositionSide {
+    pub fn try_from_str(s: &str) -> Result<Self, OrderIntentError> {
+        match s.to_lowercase().as_str() {
+            "long" => Ok(PositionSide::Long),
+            "short" => Ok(PositionSide::Short),
+            _ => Err(OrderIntentError::BadPositionSide(s.to_string())),
+        }
+    }
+
+    /// Side of the order to open the position
+    pub fn open_side(&self) -> Side {
+        match self {
+            PositionSide::Long => Side::Buy,
+            PositionSide::Short => Side::Sell,
+        }
+    }
+
+    /// Effect of the order of the side on the position
+    pub fn effect(&self, side: Side) -> PositionEffect {
+        if side == self.open_side() {
+            PositionEffect::Open
+        } else {
+            PositionEffect::Close
+        }
+    }
+}
+
+impl TryFrom<&str> for PositionSide {
+    type Error = OrderIntentError;
+
+    fn try_from(s: &str) -> Result<Self, OrderIntentError> {
+        Self::try_from_str(s)
+    }
+}
+
+impl fmt::Display for PositionEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl PositionEffect {
+    pub fn try_from_str(s: &str) -> Result<Self, OrderIntentError> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(PositionEffect::Open),
+            "close" => Ok(PositionEffect::Close),
+            _ => Err(OrderIntentError::BadPositionEffect(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<&str> for PositionEffect {
+    type Error = OrderIntentError;
+
+    fn try_from(s: &str) -> Result<Self, OrderIntentError> {
+        Self::try_from_str(s)
+    }
+}
+
+impl OrderIntent {
+    /// Hedge mode intent, effect is derived from the side
+    pub fn hedge(side: Side, position_side: PositionSide) -> Self {
+        Self {
+            position_side: Some(position_side),
+            effect: Some(position_side.effect(side)),
+            reduce_only: false,
+        }
+    }
+
+    /// One-way mode reduce-only intent
+    pub fn reduce_only() -> Self {
+        Self {
+            position_side: None,
+            effect: Some(PositionEffect::Close),
+            reduce_only: true,
+        }
+    }
+
+    /// Checks the intent is legal for the order side
+    pub fn validate(&self, side: Side) -> Result<(), OrderIntentError> {
+        if self.reduce_only && self.effect == Some(PositionEffect::Open) {
+            return Err(OrderIntentError::ReduceOnlyOpen);
+        }
+        if let Some(position_side) = self.position_side {
+            if self.reduce_only {
+                return Err(OrderIntentError::ReduceOnlyInHedgeMode);
+            }
+            if let Some(effect) = self.effect {
+                if effect != position_side.effect(side) {
+                    return Err(OrderIntentError::SideMismatch(side, effect, position_side));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intent_validate() {
+        assert!(OrderIntent::default().validate(Side::Buy).is_ok());
+        assert!(OrderIntent::reduce_only().validate(Side::Sell).is_ok());
+        let i = OrderIntent::hedge(Side::Sell, PositionSide::Long);
+        assert_eq!(i.effect, Some(PositionEffect::Close));
+        assert!(i.validate(Side::Sell).is_ok());
+        assert_eq!(
+            i.validate(Side::Buy),
+            Err(OrderIntentError::SideMismatch(
+                Side::Buy,
+                PositionEffect::Close,
+                PositionSide::Long
+            ))
+        );
+        let i = OrderIntent {
+            effect: Some(PositionEffect::Open),
+            reduce_only: true,
+            ..Default::default()
+        };
+        assert_eq!(i.validate(Side::Buy), Err(OrderIntentError::ReduceOnlyOpen));
+        let i = OrderIntent {
+            reduce_only: true,
+            ..OrderIntent::hedge(Side::Sell, PositionSide::Long)
+        };
+        assert_eq!(
+            i.validate(Side::Sell),
+            Err(OrderIntentError::ReduceOnlyInHedgeMode)
+        );
+    }
+
+    #[test]
+    fn test_intent_from_json() {
+        let i: OrderIntent =
+            serde_json::from_str(r#"{"position_side":"SHORT","effect":"Open"}"#).unwrap();
+        assert_eq!(i, OrderIntent::hedge(Side::Sell, PositionSide::Short));
+        assert_eq!(
+            serde_json::to_string(&i).unwrap(),
+            r#"{"position_side":"short","effect":"open","reduce_only":false}"#
+        );
+        assert_eq!(PositionSide::try_from("Long"), Ok(PositionSide::Long));
+    }
+}
@@ -1,12 +1,15 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{my_date_formatter, Liq, Side};
+use crate::{my_date_formatter, Liq, OrderIntent, OrderIntentError, Side};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OrderToPlace {
     pub liq: Liq,
     pub side: Side,
+    // derivative order intent, none for usual orders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<OrderIntent>,
     #[serde(with = "my_date_formatter")]
     pub ts: DateTime<Utc>,
 }
@@ -16,9 +19,23 @@ impl OrderToPlace {
         Self {
             liq,
             side,
+            intent: None,
             ts: Utc::now(),
         }
     }
+
+    /// Order with derivative intent, illegal combinations are rejected
+    pub fn with_intent(
+        liq: Liq,
+        side: Side,
+        intent: OrderIntent,
+    ) -> Result<Self, OrderIntentError> {
+        intent.validate(side)?;
+        Ok(Self {
+            intent: Some(intent),
+            ..Self::new(liq, side)
+        })
+    }
 }
 
 // https://doc.rust-lang.org/rust-by-example/testing/unit_testing.html
@@ -27,16 +44,39 @@ mod tests {
     use serde_json::json;
 
     use super::*;
-    use crate::{Amount, Price};
+    use crate::{n, Amount, PositionSide, Price};
 
     #[test]
+    #[cfg(not(feature = "decimal"))]
     fn test_order_to_place_to_json() {
-        let o = OrderToPlace::new(Liq::from_pa(Price(1.0), Amount(2.0)), Side::Sell);
+        let o = OrderToPlace::new(Liq::from_pa(Price(n(1.0)), Amount(n(2.0))), Side::Sell);
         let actual = json!(o).to_string();
         println!("{:?}", actual);
         let expected_part = r#"{"liq":{"a":2.0,"p":1.0,"w":2.0},"side":"sell"#;
         println!("{:?}", expected_part);
         let has_part = actual.contains(expected_part);
-        assert_eq!(has_part, true)
+        assert!(has_part)
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_order_to_place_to_json_decimal() {
+        let o = OrderToPlace::new(Liq::from_pa(Price(n(1.0)), Amount(n(2.0))), Side::Sell);
+        let actual = json!(o).to_string();
+        assert!(actual.contains(r#"{"liq":{"a":"2","p":"1","w":"2"},"side":"sell"#));
+    }
+
+    #[test]
+    fn test_order_to_place_with_intent() {
+        let liq = Liq::from_pa(Price(n(1.0)), Amount(n(2.0)));
+        let o = OrderToPlace::with_intent(liq, Side::Sell, OrderIntent::reduce_only()).unwrap();
+        let actual = json!(o).to_string();
+        assert!(actual.contains(r#""intent":{"effect":"close","reduce_only":true}"#));
+        let r = OrderToPlace::with_intent(
+            liq,
+            Side::Sell,
+            OrderIntent::hedge(Side::Buy, PositionSide::Short),
+        );
+        assert!(r.is_err());
     }
 }
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    my_date_formatter, Amount, Balances, Currency, Exchange, Money, MoneyError, OrderPlaced, Side,
+    Symbol, Trade, NUM_ZERO,
+};
+
+/// Funds of a currency on an exchange
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Holding {
+    pub free: Amount,
+    pub locked: Amount, // reserved by open orders
+}
+
+impl Default for Holding {
+    fn default() -> Self {
+        Self {
+            free: Amount::ZERO,
+            locked: Amount::ZERO,
+        }
+    }
+}
+
+impl Holding {
+    pub fn total(&self) -> Amount {
+        self.free + self.locked
+    }
+}
+
+/// Funds locked by an open order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub locked: Money, // remaining locked funds
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum PortfolioError {
+    #[error("insufficient funds: need {0}, free {1}")]
+    InsufficientFunds(Money, Money),
+    #[error("duplicate order {0}")]
+    DuplicateOrder(String),
+    #[error("unknown order {0}")]
+    UnknownOrder(String),
+    #[error("insufficient locked funds: need {0}, locked {1}")]
+    InsufficientLocked(Money, Money),
+    #[error("trade symbol {1} doesn't match order symbol {0}")]
+    SymbolMismatch(String, String),
+    #[error("trade side {1} doesn't match order side {0}")]
+    SideMismatch(Side, Side),
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// Portfolio keyed by (exchange, currency) with funds reserved by open orders
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Portfolio {
+    pub holdings: HashMap<Exchange, HashMap<Currency, Holding>>,
+    pub orders: HashMap<String, Reservation>, // key is order id
+}
+
+/// Row of portfolio snapshot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HoldingRow {
+    pub eg: Exchange,
+    pub c: Currency,
+    pub free: Amount,
+    pub locked: Amount,
+    pub total: Amount,
+}
+
+/// Consistent view of portfolio at the moment, rows are sorted by (eg, c)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PortfolioSnapshot {
+    pub holdings: Vec<HoldingRow>,
+    #[serde(with = "my_date_formatter")]
+    pub ts: DateTime<Utc>,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn holding(&self, eg: &str, c: &str) -> Holding {
+        self.holdings
+            .get(eg)
+            .and_then(|m| m.get(&c.to_uppercase()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets free funds, as received from exchange balances
+    pub fn set_free(&mut self, eg: &str, c: &str, free: Amount) {
+        self.holding_mut(eg, c).free = free;
+    }
+
+    /// Adds free funds, negative amount withdraws (not more than free funds)
+    pub fn deposit(&mut self, eg: &str, m: &Money) -> Result<(), PortfolioError> {
+        let h = self.holding_mut(eg, &m.c);
+        if h.free.0 + m.v < NUM_ZERO {
+            let need = Money::new(m.c.clone(), -m.v);
+            let free = Money::new(m.c.clone(), h.free.0);
+            return Err(PortfolioError::InsufficientFunds(need, free));
+        }
+        h.free += m.amount();
+        Ok(())
+    }
+
+    /// Total funds of the exchange
+    pub fn balances(&self, eg: &str) -> Balances {
+        self.holdings
+            .get(eg)
+            .map(|m| m.iter().map(|(c, h)| (c.clone(), h.total())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Locks funds of the placed order: quote worth for buy, base amount for sell
+    pub fn place(&mut self, o: &OrderPlaced) -> Result<(), PortfolioError> {
+        if self.orders.contains_key(&o.id) {
+            return Err(PortfolioError::DuplicateOrder(o.id.clone()));
+        }
+        let need = match o.side {
+            Side::Buy => o.liq.quote_money(&o.symbol),
+            Side::Sell => o.liq.base_money(&o.symbol),
+        };
+        let h = self.holding_mut(&o.symbol.eg, &need.c);
+        if h.free < need.amount() {
+            let free = Money::new(need.c.clone(), h.free.0);
+            return Err(PortfolioError::InsufficientFunds(need, free));
+        }
+        h.free -= need.amount();
+        h.locked += need.amount();
+        self.orders.insert(
+            o.id.clone(),
+            Reservation {
+                symbol: o.symbol.clone(),
+                side: o.side,
+                locked: need,
+            },
+        );
+        Ok(())
+    }
+
+    /// Applies a fill of the order: spends locked funds
+    /// (free ones if the locked are not enough) and receives the other currency.
+    /// The trade must be of the order symbol and side, funds never go negative
+    pub fn fill(&mut self, order_id: &str, t: &Trade) -> Result<(), PortfolioError> {
+        let r = self
+            .orders
+            .get(order_id)
+            .ok_or_else(|| PortfolioError::UnknownOrder(order_id.to_string()))?;
+        if t.symbol != r.symbol {
+            return Err(PortfolioError::SymbolMismatch(
+                r.symbol.to_string(),
+                t.symbol.to_string(),
+            ));
+        }
+        if t.side != r.side {
+            return Err(PortfolioError::SideMismatch(r.side, t.side));
+        }
+        let (spent, received) = match r.side {
+            Side::Buy => (t.liq.quote_money(&t.symbol), t.liq.base_money(&t.symbol)),
+            Side::Sell => (t.liq.base_money(&t.symbol), t.liq.quote_money(&t.symbol)),
+        };
+        let from_locked = Money::new(spent.c.clone(), spent.amount().min(r.locked.amount()).0);
+        let from_free = spent.checked_sub(&from_locked)?;
+        let remains = r.locked.checked_sub(&from_locked)?;
+        let eg = r.symbol.eg.clone();
+        let h = self.holding(&eg, &spent.c);
+        if h.locked < from_locked.amount() {
+            let locked = Money::new(spent.c.clone(), h.locked.0);
+            return Err(PortfolioError::InsufficientLocked(from_locked, locked));
+        }
+        if h.free < from_free.amount() {
+            let free = Money::new(spent.c.clone(), h.free.0);
+            return Err(PortfolioError::InsufficientFunds(from_free, free));
+        }
+        if let Some(r) = self.orders.get_mut(order_id) {
+            r.locked = remains;
+        }
+        let h = self.holding_mut(&eg, &spent.c);
+        h.locked -= from_locked.amount();
+        h.free -= from_free.amount();
+        self.deposit(&eg, &received)
+    }
+
+    /// Unlocks the remaining funds of the order (on cancel or when it's done)
+    pub fn release(&mut self, order_id: &str) -> Result<Money, PortfolioError> {
+        let r = self
+            .orders
+            .get(order_id)
+            .ok_or_else(|| PortfolioError::UnknownOrder(order_id.to_string()))?;
+        let (eg, locked) = (r.symbol.eg.clone(), r.locked.clone());
+        let h = self.holding(&eg, &locked.c);
+        if h.locked < locked.amount() {
+            let h_locked = Money::new(locked.c.clone(), h.locked.0);
+            return Err(PortfolioError::InsufficientLocked(locked, h_locked));
+        }
+        self.orders.remove(order_id);
+        let h = self.holding_mut(&eg, &locked.c);
+        h.locked -= locked.amount();
+        h.free += locked.amount();
+        Ok(locked)
+    }
+
+    pub fn snapshot(&self) -> PortfolioSnapshot {
+        let mut holdings: Vec<HoldingRow> = self
+            .holdings
+            .iter()
+            .flat_map(|(eg, m)| {
+                m.iter().map(move |(c, h)| HoldingRow {
+                    eg: eg.clone(),
+                    c: c.clone(),
+                    free: h.free,
+                    locked: h.locked,
+                    total: h.total(),
+                })
+            })
+            .collect();
+        holdings.sort_by(|x, y| (&x.eg, &x.c).cmp(&(&y.eg, &y.c)));
+        PortfolioSnapshot {
+            holdings,
+            ts: Utc::now(),
+        }
+    }
+
+    // private helpers
+
+    fn holding_mut(&mut self, eg: &str, c: &str) -> &mut Holding {
+        self.holdings
+            .entry(eg.to_string())
+            .or_default()
+            .entry(c.to_uppercase())
+            .or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{btcusdt, n, Liq, Price};
+
+    fn order(id: &str, side: Side, liq: Liq) -> OrderPlaced {
+        OrderPlaced {
+            id: id.into(),
+            symbol: btcusdt(),
+            kind: None,
+            liq,
+            side,
+            ts: Utc::now(),
+        }
+    }
+
+    fn trade(liq: Liq, side: Side) -> Trade {
+        Trade {
+            id: "t1".into(),
+            symbol: btcusdt(),
+            kind: None,
+            liq,
+            side,
+            ts: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_portfolio_buy() {
+        let mut pf = Portfolio::new();
+        pf.deposit("eg", &Money::new("usdt".into(), n(1000.0)))
+            .unwrap();
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(2.0)));
+        pf.place(&order("o1", Side::Buy, liq)).unwrap();
+        assert_eq!(pf.holding("eg", "usdt").locked, Amount(n(200.0)));
+        assert_eq!(pf.holding("eg", "usdt").free, Amount(n(800.0)));
+
+        pf.fill(
+            "o1",
+            &trade(Liq::from_pa(Price(n(100.0)), Amount(n(1.0))), Side::Buy),
+        )
+        .unwrap();
+        assert_eq!(pf.holding("eg", "btc").free, Amount(n(1.0)));
+        assert_eq!(pf.holding("eg", "usdt").locked, Amount(n(100.0)));
+
+        let released = pf.release("o1").unwrap();
+        assert_eq!(released, Money::new("usdt".into(), n(100.0)));
+        assert_eq!(pf.holding("eg", "usdt").total(), Amount(n(900.0)));
+        assert_eq!(pf.balances("eg").get("BTC"), Some(&Amount(n(1.0))));
+    }
+
+    #[test]
+    fn test_portfolio_insufficient() {
+        let mut pf = Portfolio::new();
+        pf.set_free("eg", "btc", Amount(n(1.0)));
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(2.0)));
+        let r = pf.place(&order("o1", Side::Sell, liq));
+        assert!(matches!(r, Err(PortfolioError::InsufficientFunds(..))));
+        assert_eq!(
+            pf.fill("o2", &trade(liq, Side::Sell)),
+            Err(PortfolioError::UnknownOrder("o2".into()))
+        );
+    }
+
+    #[test]
+    fn test_portfolio_withdraw_release_checks() {
+        let mut pf = Portfolio::new();
+        assert!(matches!(
+            pf.deposit("eg", &Money::new("usdt".into(), n(-100.0))),
+            Err(PortfolioError::InsufficientFunds(..))
+        ));
+        assert_eq!(pf.holding("eg", "usdt").free, Amount::ZERO);
+        pf.set_free("eg", "usdt", Amount(n(100.0)));
+        pf.deposit("eg", &Money::new("usdt".into(), n(-40.0)))
+            .unwrap();
+        assert_eq!(pf.holding("eg", "usdt").free, Amount(n(60.0)));
+
+        pf.set_free("eg", "btc", Amount(n(1.0)));
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(1.0)));
+        pf.place(&order("o1", Side::Sell, liq)).unwrap();
+        pf.holdings
+            .get_mut("eg")
+            .unwrap()
+            .get_mut("BTC")
+            .unwrap()
+            .locked = Amount::ZERO;
+        assert!(matches!(
+            pf.release("o1"),
+            Err(PortfolioError::InsufficientLocked(..))
+        ));
+        assert!(pf.orders.contains_key("o1"));
+    }
+
+    #[test]
+    fn test_portfolio_fill_checks() {
+        let mut pf = Portfolio::new();
+        pf.set_free("eg", "btc", Amount(n(1.0)));
+        let liq = Liq::from_pa(Price(n(100.0)), Amount(n(1.0)));
+        pf.place(&order("o1", Side::Sell, liq)).unwrap();
+        assert_eq!(
+            pf.fill("o1", &trade(liq, Side::Buy)),
+            Err(PortfolioError::SideMismatch(Side::Sell, Side::Buy))
+        );
+        let mut t = trade(liq, Side::Sell);
+        t.symbol = Symbol::new("eg".into(), "eth".into(), "usdt".into());
+        assert_eq!(
+            pf.fill("o1", &t),
+            Err(PortfolioError::SymbolMismatch(
+                "eg:BTC/USDT".into(),
+                "eg:ETH/USDT".into()
+            ))
+        );
+        let big = Liq::from_pa(Price(n(100.0)), Amount(n(2.0)));
+        assert!(matches!(
+            pf.fill("o1", &trade(big, Side::Sell)),
+            Err(PortfolioError::InsufficientFunds(..))
+        ));
+        // nothing has changed on errors
+        assert_eq!(pf.holding("eg", "btc").locked, Amount(n(1.0)));
+        assert_eq!(pf.holding("eg", "usdt").free, Amount::ZERO);
+    }
+
+    #[test]
+    #[cfg(not(feature = "decimal"))]
+    fn test_portfolio_snapshot_to_json() {
+        let mut pf = Portfolio::new();
+        pf.set_free("eg", "btc", Amount(n(1.0)));
+        let s = serde_json::to_string(&pf.snapshot()).unwrap();
+        assert!(s.starts_with(
+            r#"{"holdings":[{"eg":"eg","c":"BTC","free":1.0,"locked":0.0,"total":1.0}],"ts":"#
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_portfolio_snapshot_to_json_decimal() {
+        let mut pf = Portfolio::new();
+        pf.set_free("eg", "btc", Amount(n(1.0)));
+        let s = serde_json::to_string(&pf.snapshot()).unwrap();
+        assert!(s.starts_with(
+            r#"{"holdings":[{"eg":"eg","c":"BTC","free":"1","locked":"0","total":"1"}],"ts":"#
+        ));
+    }
+}
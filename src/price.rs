@@ -1,25 +1,82 @@
 use core::ops;
-use std::cmp::{Eq, PartialEq, PartialOrd};
-// use std::cmp::{Ord, Ordering};
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 use std::hash::{Hash, Hasher};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{check_f64, check_num, deserialize_num, num_cmp, Num, NumError, NUM_ZERO};
 
 // Price (exchange rate) for base/quote
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialOrd, PartialEq)]
-pub struct Price(pub f64); // NaN, inf are not allowed
+#[derive(Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct Price(pub Num); // NaN, inf are not allowed
+
+impl Price {
+    pub const ZERO: Self = Self(NUM_ZERO);
+
+    /// Checked constructor: NaN, inf and negative values are not allowed
+    pub fn new(v: Num) -> Result<Self, NumError> {
+        check_num(v).map(Self)
+    }
+}
 
 impl Hash for Price {
+    #[cfg(not(feature = "decimal"))]
     fn hash<H>(&self, state: &mut H)
     where
         H: Hasher,
     {
-        self.0.to_bits().hash(state)
+        // + 0.0 turns -0.0 into 0.0, as they are equal
+        (self.0 + 0.0).to_bits().hash(state)
+    }
+
+    // Decimal hash is normalized, so 1.0 and 1.00 are the same key
+    #[cfg(feature = "decimal")]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.0.hash(state)
     }
 }
 
 impl Eq for Price {}
 
+// for min, max, sort and BTreeMap keys
+impl Ord for Price {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        num_cmp(self.0, rhs.0)
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl From<Price> for Num {
+    fn from(v: Price) -> Num {
+        v.0
+    }
+}
+
+impl TryFrom<f64> for Price {
+    type Error = NumError;
+
+    fn try_from(v: f64) -> Result<Self, NumError> {
+        check_f64(v).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Self::new(deserialize_num(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
 impl ops::Sub<Price> for Price {
     type Output = Self;
     fn sub(self, rhs: Price) -> Self {
@@ -46,21 +103,21 @@ impl ops::SubAssign<Price> for Price {
     }
 }
 
-impl ops::Mul<f64> for Price {
+impl ops::Mul<Num> for Price {
     type Output = Self;
-    fn mul(self, rhs: f64) -> Self {
+    fn mul(self, rhs: Num) -> Self {
         Self(self.0 * rhs)
     }
 }
 
-impl ops::Div<f64> for Price {
+impl ops::Div<Num> for Price {
     type Output = Self;
-    fn div(self, rhs: f64) -> Self {
+    fn div(self, rhs: Num) -> Self {
         Self(self.0 / rhs)
     }
 }
 
-impl ops::Div<Price> for f64 {
+impl ops::Div<Price> for Num {
     type Output = Price;
     fn div(self, rhs: Price) -> Price {
         Price(self / rhs.0)
@@ -68,16 +125,39 @@ impl ops::Div<Price> for f64 {
 }
 
 impl ops::Div<Price> for Price {
-    type Output = f64;
-    fn div(self, rhs: Price) -> f64 {
+    type Output = Num;
+    fn div(self, rhs: Price) -> Num {
         self.0 / rhs.0
     }
 }
 
-// for min, max
-// https://www.reddit.com/r/rust/comments/29kia3/no_ord_for_f32/
-// impl Ord for Price {
-//     fn cmp(&self, rhs: &Self) -> Ordering {
-//         self.0.partial_cmp(&rhs.0).unwrap_or(Ordering::Equal)
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::n;
+
+    #[test]
+    fn test_price_btreemap_key() {
+        let mut m = BTreeMap::new();
+        m.insert(Price(n(2.0)), 1);
+        m.insert(Price(n(1.0)), 2);
+        assert_eq!(m.keys().next(), Some(&Price(n(1.0))));
+    }
+
+    #[test]
+    fn test_price_bincode() {
+        let bytes = bincode::serialize(&Price(n(27123.1))).unwrap();
+        let p: Price = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(p, Price(n(27123.1)));
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_price_out_of_range() {
+        assert_eq!(Price::try_from(1e30), Err(NumError::OutOfRange(1e30)));
+        assert!(serde_json::from_str::<Price>("1e30").is_err());
+        assert!(serde_json::from_str::<Price>("1e20").is_ok());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    my_date_formatter, num_from_f64, Currency, Depth, Depths, Money, Num, Price, Spread,
+    SpreadError, Symbol, NUM_ONE,
+};
+
+/// Which price of a spread to use for conversion
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateKind {
+    Mid,
+    Bid,
+    Ask,
+    Exec, // as executable: sell base at bid, buy base at ask
+}
+
+/// Spread of a symbol with the time it was received
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateSource {
+    pub symbol: Symbol,
+    pub spread: Spread,
+    #[serde(with = "my_date_formatter")]
+    pub ts: DateTime<Utc>,
+}
+
+/// Conversion from one currency to another through the path of symbols
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub from: Currency,
+    pub to: Currency,
+    pub rate: Num, // units of `to` for 1 unit of `from`
+    pub path: Vec<Symbol>,
+    pub oldest_ts: Option<DateTime<Utc>>, // None for the same currency
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum RatesError {
+    #[error("no conversion path {0} -> {1}")]
+    NoPath(Currency, Currency),
+}
+
+/// Conversion graph: currencies are nodes, spreads of symbols are edges
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Rates {
+    pub sources: HashMap<String, RateSource>, // key is str(symbol)
+}
+
+impl Conversion {
+    pub fn convert(&self, v: Num) -> Num {
+        v * self.rate
+    }
+
+    /// Age of the oldest spread used, zero for the same currency
+    pub fn staleness(&self, now: DateTime<Utc>) -> Duration {
+        self.oldest_ts.map_or(Duration::zero(), |ts| now - ts)
+    }
+}
+
+impl Rates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn upsert(&mut self, symbol: Symbol, spread: Spread, ts: DateTime<Utc>) {
+        self.sources
+            .insert(symbol.to_string(), RateSource { symbol, spread, ts });
+    }
+
+    pub fn upsert_depth(
+        &mut self,
+        symbol: Symbol,
+        depth: &Depth,
+        ts: DateTime<Utc>,
+    ) -> Result<(), SpreadError> {
+        let spread = Spread::try_from(depth)?;
+        self.upsert(symbol, spread, ts);
+        Ok(())
+    }
+
+    /// Rates of depths keyed by str(symbol), other keys and empty depths are skipped
+    pub fn from_depths(depths: &Depths, ts: DateTime<Utc>) -> Self {
+        let mut rates = Self::new();
+        for (key, depth) in &depths.vecs {
+            if let Ok(symbol) = Symbol::try_from(key.as_str()) {
+                let _ = rates.upsert_depth(symbol, depth, ts);
+            }
+        }
+        rates
+    }
+
+    /// Conversion through the shortest path (fewest symbols),
+    /// the best rate is chosen among the shortest paths
+    pub fn conversion(
+        &self,
+        from: &Currency,
+        to: &Currency,
+        kind: RateKind,
+    ) -> Result<Conversion, RatesError> {
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        let mut visited: HashSet<Currency> = HashSet::from([from.clone()]);
+        let mut frontier: HashMap<Currency, (Num, Vec<&RateSource>)> =
+            HashMap::from([(from.clone(), (NUM_ONE, vec![]))]);
+        while !frontier.is_empty() {
+            if let Some((rate, path)) = frontier.remove(&to) {
+                return Ok(Conversion {
+                    from,
+                    to,
+                    rate,
+                    path: path.iter().map(|s| s.symbol.clone()).collect(),
+                    oldest_ts: path.iter().map(|s| s.ts).min(),
+                });
+            }
+            let mut next: HashMap<Currency, (Num, Vec<&RateSource>)> = HashMap::new();
+            for (c, (rate, path)) in &frontier {
+                for src in self.sources.values() {
+                    let Some((c_next, edge_rate)) = Self::edge(src, c, kind) else {
+                        continue;
+                    };
+                    if visited.contains(&c_next) {
+                        continue;
+                    }
+                    let rate_next = *rate * edge_rate;
+                    let better = match next.get(&c_next) {
+                        Some((r, _)) => *r < rate_next,
+                        None => true,
+                    };
+                    if better {
+                        let mut path_next = path.clone();
+                        path_next.push(src);
+                        next.insert(c_next, (rate_next, path_next));
+                    }
+                }
+            }
+            visited.extend(next.keys().cloned());
+            frontier = next;
+        }
+        Err(RatesError::NoPath(from, to))
+    }
+
+    /// Converts the money into the currency
+    pub fn convert(
+        &self,
+        m: &Money,
+        to: &Currency,
+        kind: RateKind,
+    ) -> Result<(Money, Conversion), RatesError> {
+        let conv = self.conversion(&m.c, to, kind)?;
+        Ok((Money::new(conv.to.clone(), conv.convert(m.v)), conv))
+    }
+
+    // private helpers
+
+    // currency reachable from c through the source and the rate of this step
+    fn edge(src: &RateSource, c: &Currency, kind: RateKind) -> Option<(Currency, Num)> {
+        let (ask, bid) = (src.spread.best_ask.price(), src.spread.best_bid.price());
+        let mid = (ask + bid) / num_from_f64(2.0);
+        let price = |sell_base: bool| -> Price {
+            match kind {
+                RateKind::Mid => mid,
+                RateKind::Bid => bid,
+                RateKind::Ask => ask,
+                RateKind::Exec if sell_base => bid,
+                RateKind::Exec => ask,
+            }
+        };
+        if *c == src.symbol.bs {
+            Some((src.symbol.qt.clone(), price(true).0))
+        } else if *c == src.symbol.qt {
+            let p = price(false);
+            if p == Price::ZERO {
+                return None;
+            }
+            Some((src.symbol.bs.clone(), NUM_ONE / p.0))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{n, Amount, Liq};
+
+    fn spread(ask: f64, bid: f64) -> Spread {
+        Spread::new(
+            Liq::from_pa(Price(n(ask)), Amount(n(1.0))),
+            Liq::from_pa(Price(n(bid)), Amount(n(1.0))),
+        )
+    }
+
+    fn rates() -> Rates {
+        let t0 = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let t1 = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 10).unwrap();
+        let mut r = Rates::new();
+        let eth_btc = Symbol::try_from("eg:ETH/BTC").unwrap();
+        let btc_usdt = Symbol::try_from("eg:BTC/USDT").unwrap();
+        r.upsert(eth_btc, spread(0.0625, 0.0615), t0);
+        r.upsert(btc_usdt, spread(20000.0, 19000.0), t1);
+        r
+    }
+
+    #[test]
+    fn test_conversion_path() {
+        let c = rates()
+            .conversion(&"eth".into(), &"usdt".into(), RateKind::Bid)
+            .unwrap();
+        assert_eq!(c.rate, n(0.0615) * n(19000.0));
+        assert_eq!(c.path.len(), 2);
+        assert_eq!(c.path[0].to_string(), "eg:ETH/BTC");
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 0, 1, 0).unwrap();
+        assert_eq!(c.staleness(now), Duration::seconds(60));
+    }
+
+    #[test]
+    fn test_conversion_inverse() {
+        let r = rates();
+        let m = Money::new("usdt".into(), n(20000.0));
+        let (m2, c) = r.convert(&m, &"btc".into(), RateKind::Exec).unwrap();
+        assert_eq!(m2, Money::new("BTC".into(), n(1.0)));
+        assert_eq!(c.path.len(), 1);
+    }
+
+    #[test]
+    fn test_conversion_no_path() {
+        let r = rates();
+        assert_eq!(
+            r.conversion(&"eth".into(), &"xrp".into(), RateKind::Mid),
+            Err(RatesError::NoPath("ETH".into(), "XRP".into()))
+        );
+        let c = r
+            .conversion(&"btc".into(), &"btc".into(), RateKind::Mid)
+            .unwrap();
+        assert_eq!(c.rate, n(1.0));
+        assert!(c.path.is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use core::ops;
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{num_cmp, num_from_f64, Amount, Num, Price, Worth, NUM_ONE, NUM_ZERO};
+
+/// Ratio (relative quantity) stored as a fraction: 0.01 = 1% = 100 bps.
+/// Use it for fees, slippage, thresholds instead of bare floats
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Ratio(pub Num);
+
+impl Eq for Ratio {}
+
+impl Ord for Ratio {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        num_cmp(self.0, rhs.0)
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ratio {
+    pub const ZERO: Self = Self(NUM_ZERO);
+    pub const ONE: Self = Self(NUM_ONE);
+
+    pub fn from_fraction(v: Num) -> Self {
+        Self(v)
+    }
+
+    pub fn from_percent(v: Num) -> Self {
+        Self(v / num_from_f64(100.0))
+    }
+
+    pub fn from_bps(v: Num) -> Self {
+        Self(v / num_from_f64(10_000.0))
+    }
+
+    pub fn fraction(&self) -> Num {
+        self.0
+    }
+
+    pub fn percent(&self) -> Num {
+        self.0 * num_from_f64(100.0)
+    }
+
+    pub fn bps(&self) -> Num {
+        self.0 * num_from_f64(10_000.0)
+    }
+}
+
+impl ops::Add<Ratio> for Ratio {
+    type Output = Self;
+    fn add(self, rhs: Ratio) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl ops::Sub<Ratio> for Ratio {
+    type Output = Self;
+    fn sub(self, rhs: Ratio) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl ops::Neg for Ratio {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+// for Price * (n(1.0) + Ratio)
+impl ops::Add<Ratio> for Num {
+    type Output = Ratio;
+    fn add(self, rhs: Ratio) -> Ratio {
+        Ratio(self + rhs.0)
+    }
+}
+
+// for Price * (n(1.0) - Ratio)
+impl ops::Sub<Ratio> for Num {
+    type Output = Ratio;
+    fn sub(self, rhs: Ratio) -> Ratio {
+        Ratio(self - rhs.0)
+    }
+}
+
+impl ops::Mul<Ratio> for Ratio {
+    type Output = Self;
+    fn mul(self, rhs: Ratio) -> Self {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl ops::Mul<Ratio> for Price {
+    type Output = Price;
+    fn mul(self, rhs: Ratio) -> Price {
+        Price(self.0 * rhs.0)
+    }
+}
+
+impl ops::Mul<Ratio> for Amount {
+    type Output = Amount;
+    fn mul(self, rhs: Ratio) -> Amount {
+        Amount(self.0 * rhs.0)
+    }
+}
+
+impl ops::Mul<Ratio> for Worth {
+    type Output = Worth;
+    fn mul(self, rhs: Ratio) -> Worth {
+        Worth(self.0 * rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::n;
+
+    #[test]
+    fn test_ratio_conversions() {
+        let r = Ratio::from_bps(n(25.0));
+        assert_eq!(r.fraction(), n(0.0025));
+        assert_eq!(r.percent(), n(0.25));
+        assert_eq!(Ratio::from_percent(n(1.0)), Ratio::from_bps(n(100.0)));
+    }
+
+    #[test]
+    fn test_price_mul_ratio() {
+        let p = Price(n(100.0)) * (n(1.0) + Ratio::from_percent(n(1.0)));
+        assert_eq!(p, Price(n(101.0)));
+        let w = Worth(n(100.0)) * (n(1.0) - Ratio::from_percent(n(10.0)));
+        assert_eq!(w, Worth(n(90.0)));
+    }
+}
@@ -3,6 +3,8 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{Num, Price, NUM_ONE};
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Side {
     #[serde(rename(serialize = "buy", deserialize = "buy"))]
@@ -51,6 +53,35 @@ impl Side {
             Side::Sell => Side::Buy,
         }
     }
+
+    /// 1 for buy, -1 for sell
+    pub fn sign(&self) -> Num {
+        match self {
+            Side::Buy => NUM_ONE,
+            Side::Sell => -NUM_ONE,
+        }
+    }
+
+    /// Is price p better (more aggressive in the book) than other:
+    /// higher for buy, lower for sell
+    pub fn is_better(&self, p: Price, other: Price) -> bool {
+        match self {
+            Side::Buy => p > other,
+            Side::Sell => p < other,
+        }
+    }
+
+    /// Moves price by tick toward the opposite side (more aggressive),
+    /// None if the price would be negative
+    pub fn improve(&self, p: Price, tick: Price) -> Option<Price> {
+        Price::new(p.0 + tick.0 * self.sign()).ok()
+    }
+
+    /// Moves price by tick away from the opposite side (less aggressive),
+    /// None if the price would be negative
+    pub fn worsen(&self, p: Price, tick: Price) -> Option<Price> {
+        Price::new(p.0 - tick.0 * self.sign()).ok()
+    }
 }
 
 impl TryFrom<&str> for Side {
@@ -66,6 +97,7 @@ mod tests {
     use serde_json::json;
 
     use super::*;
+    use crate::n;
     use std::collections::HashMap;
 
     #[test]
@@ -90,7 +122,7 @@ mod tests {
     fn test_side_sell_from_json() {
         let m: HashMap<&str, Side> = serde_json::from_str(r#"{"side":"sell"}"#).unwrap();
         if let Some(v) = m.get(&"side") {
-            assert_eq!(v.is_buy(), false)
+            assert!(!v.is_buy())
         } else {
             panic!("{:?}", m);
         }
@@ -106,6 +138,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_side_price_helpers() {
+        assert!(Side::Buy.is_better(Price(n(2.0)), Price(n(1.0))));
+        assert!(Side::Sell.is_better(Price(n(1.0)), Price(n(2.0))));
+        assert!(!Side::Sell.is_better(Price(n(1.0)), Price(n(1.0))));
+        assert_eq!(
+            Side::Buy.improve(Price(n(10.0)), Price(n(0.5))),
+            Some(Price(n(10.5)))
+        );
+        assert_eq!(
+            Side::Sell.improve(Price(n(10.0)), Price(n(0.5))),
+            Some(Price(n(9.5)))
+        );
+        assert_eq!(
+            Side::Sell.worsen(Price(n(10.0)), Price(n(0.5))),
+            Some(Price(n(10.5)))
+        );
+        assert_eq!(Side::Sell.improve(Price(n(0.2)), Price(n(0.5))), None);
+        assert_eq!(Side::Buy.worsen(Price(n(0.2)), Price(n(0.5))), None);
+        assert_eq!(Side::Sell.sign(), n(-1.0));
+    }
+
     #[test]
     #[should_panic]
     fn test_side_unknown_from_json() {
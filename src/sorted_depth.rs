@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, Depth, Liq, Price, Side, Spread};
+
+/// Sorted depth (order book) for incremental feeds:
+/// level updates in O(log n), best bid/ask in O(1), iteration in price order without allocations.
+/// Serialized as Depth
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(from = "Depth", into = "Depth")]
+pub struct SortedDepth {
+    asks: BTreeMap<Price, Liq>,
+    bids: BTreeMap<Price, Liq>,
+    best_ask: Option<Liq>,
+    best_bid: Option<Liq>,
+}
+
+impl SortedDepth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn replace(&mut self, depth: Depth) {
+        *self = depth.into();
+    }
+
+    /// Sets the level of the book side (bids for buy, asks for sell), zero amount removes it
+    pub fn update_level(&mut self, side: Side, liq: Liq) {
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        if liq.amount() == Amount::ZERO {
+            levels.remove(&liq.price());
+        } else {
+            levels.insert(liq.price(), liq);
+        }
+        match side {
+            Side::Buy => self.best_bid = self.bids.last_key_value().map(|(_, &l)| l),
+            Side::Sell => self.best_ask = self.asks.first_key_value().map(|(_, &l)| l),
+        }
+    }
+
+    /// Applies depth change (delta), zero amounts remove levels
+    pub fn update(&mut self, depth_change: &Depth) {
+        for &l in &depth_change.asks {
+            self.update_level(Side::Sell, l);
+        }
+        for &l in &depth_change.bids {
+            self.update_level(Side::Buy, l);
+        }
+    }
+
+    pub fn best_ask(&self) -> Option<Liq> {
+        self.best_ask
+    }
+
+    pub fn best_bid(&self) -> Option<Liq> {
+        self.best_bid
+    }
+
+    pub fn spread(&self) -> Option<Spread> {
+        Some(Spread::new(self.best_ask?, self.best_bid?))
+    }
+
+    /// Asks in ascending price order
+    pub fn asks(&self) -> impl DoubleEndedIterator<Item = &Liq> + ExactSizeIterator {
+        self.asks.values()
+    }
+
+    /// Bids in descending price order
+    pub fn bids(&self) -> impl DoubleEndedIterator<Item = &Liq> + ExactSizeIterator {
+        self.bids.values().rev()
+    }
+
+    pub fn get(&self, side: Side, p: Price) -> Option<&Liq> {
+        match side {
+            Side::Buy => self.bids.get(&p),
+            Side::Sell => self.asks.get(&p),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.asks.is_empty() && self.bids.is_empty()
+    }
+
+    pub fn to_depth(&self) -> Depth {
+        Depth {
+            asks: self.asks().copied().collect(),
+            bids: self.bids().copied().collect(),
+        }
+    }
+}
+
+impl From<Depth> for SortedDepth {
+    fn from(depth: Depth) -> Self {
+        let mut d = Self::new();
+        d.update(&depth);
+        d
+    }
+}
+
+impl From<SortedDepth> for Depth {
+    fn from(d: SortedDepth) -> Self {
+        d.to_depth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{liq, n};
+
+    #[test]
+    fn test_sorted_depth_update() {
+        let mut d = SortedDepth::from(Depth {
+            asks: vec![liq(12.0, 1.0), liq(11.0, 1.0)],
+            bids: vec![liq(9.0, 1.0), liq(10.0, 1.0)],
+        });
+        assert_eq!(d.best_ask(), Some(liq(11.0, 1.0)));
+        assert_eq!(d.best_bid(), Some(liq(10.0, 1.0)));
+        d.update(&Depth {
+            asks: vec![liq(11.0, 0.0), liq(13.0, 2.0)],
+            bids: vec![liq(10.5, 3.0)],
+        });
+        assert_eq!(d.best_ask(), Some(liq(12.0, 1.0)));
+        assert_eq!(d.best_bid(), Some(liq(10.5, 3.0)));
+        let bids: Vec<Price> = d.bids().map(|l| l.price()).collect();
+        assert_eq!(bids, vec![Price(n(10.5)), Price(n(10.0)), Price(n(9.0))]);
+        assert_eq!(d.asks().len(), 2);
+        assert_eq!(d.spread().unwrap().delta_abs(), Price(n(1.5)));
+    }
+
+    #[test]
+    fn test_sorted_depth_json() {
+        let d = SortedDepth::from(Depth {
+            asks: vec![liq(11.0, 1.0)],
+            bids: vec![],
+        });
+        let v = json!(d);
+        assert_eq!(v, json!(d.to_depth()));
+        let d2: SortedDepth = serde_json::from_value(v).unwrap();
+        assert_eq!(d2.best_ask(), d.best_ask());
+        assert!(d2.best_bid().is_none());
+    }
+}
@@ -1,6 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::{Depth, Liq, Price};
+use crate::{deserialize_num, num_from_f64, CrossState, Depth, Liq, Price, Ratio, Side};
 
 /// Spread of depth with convininent calcs.
 /// Very suitable for a depth after depth_util::drop_worth
@@ -8,8 +8,9 @@ use crate::{Depth, Liq, Price};
 pub struct Spread {
     pub best_ask: Liq,
     pub best_bid: Liq,
+    #[serde(deserialize_with = "deserialize_delta_abs")]
     delta_abs: Price,
-    delta_rel: f64,
+    delta_rel: Ratio,
 }
 
 impl Spread {
@@ -24,38 +25,57 @@ impl Spread {
     }
 
     pub fn from_depth(depth: &Depth) -> Option<Self> {
-        if depth.asks.is_empty() || depth.bids.is_empty() {
-            return None;
-        }
-        let best_ask = depth.asks[0];
-        let best_bid = depth.bids[0];
+        let best_ask = *depth.side(Side::Sell).first()?;
+        let best_bid = *depth.side(Side::Buy).first()?;
         Some(Self::new(best_ask, best_bid))
     }
 
+    /// Like from_depth, but refuses crossed depth (best bid > best ask)
+    pub fn from_depth_checked(depth: &Depth) -> Result<Self, SpreadError> {
+        let spread = Self::from_depth(depth).ok_or(SpreadError::EmptyDepth)?;
+        if depth.cross_state() == CrossState::Crossed {
+            return Err(SpreadError::CrossedDepth(
+                spread.best_bid.price(),
+                spread.best_ask.price(),
+            ));
+        }
+        Ok(spread)
+    }
+
     pub fn delta_abs(&self) -> Price {
         self.delta_abs
     }
 
-    pub fn delta_rel(&self) -> f64 {
+    pub fn delta_rel(&self) -> Ratio {
         self.delta_rel
     }
 
     // private helpers
 
-    fn deltas(ask: Liq, bid: Liq) -> (Price, f64) {
+    fn deltas(ask: Liq, bid: Liq) -> (Price, Ratio) {
         let ask_price = ask.price();
         let bid_price = bid.price();
         let delta_abs: Price = ask_price - bid_price;
-        let mid = (ask_price + bid_price) / 2.0;
-        let delta_rel = delta_abs / mid;
+        let mid = (ask_price + bid_price) / num_from_f64(2.0);
+        let delta_rel = Ratio(delta_abs / mid);
         (delta_abs, delta_rel)
     }
 }
 
+// delta is negative for crossed books, so it's not checked as a regular price
+fn deserialize_delta_abs<'de, D>(deserializer: D) -> Result<Price, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Price(deserialize_num(deserializer)?))
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum SpreadError {
     #[error("empty depth")]
     EmptyDepth,
+    #[error("crossed depth: best bid {0:?} > best ask {1:?}")]
+    CrossedDepth(Price, Price),
 }
 
 impl TryFrom<&Depth> for Spread {
@@ -71,16 +91,56 @@ mod tests {
     use serde_json::json;
 
     use super::*;
-    use crate::Amount;
+    use crate::{n, Amount};
 
     #[test]
+    #[cfg(not(feature = "decimal"))]
     fn spread_to_json() {
         let s = Spread::new(
-            Liq::from_pa(Price(10.0), Amount(1.0)),
-            Liq::from_pa(Price(9.0), Amount(1.0)),
+            Liq::from_pa(Price(n(10.0)), Amount(n(1.0))),
+            Liq::from_pa(Price(n(9.0)), Amount(n(1.0))),
         );
         let v = json!(s);
         let expected = r#"{"best_ask":{"a":1.0,"p":10.0,"w":10.0},"best_bid":{"a":1.0,"p":9.0,"w":9.0},"delta_abs":1.0,"delta_rel":0.10526315789473684}"#;
         assert_eq!(v.to_string().as_str(), expected);
     }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn spread_to_json_decimal() {
+        let s = Spread::new(
+            Liq::from_pa(Price(n(10.0)), Amount(n(1.0))),
+            Liq::from_pa(Price(n(9.0)), Amount(n(1.0))),
+        );
+        let v = json!(s);
+        let expected = r#"{"best_ask":{"a":"1","p":"10","w":"10"},"best_bid":{"a":"1","p":"9","w":"9"},"delta_abs":"1","delta_rel":"0.1052631578947368421052631579"}"#;
+        assert_eq!(v.to_string().as_str(), expected);
+    }
+
+    #[test]
+    fn crossed_spread_from_depth() {
+        let d = Depth {
+            asks: vec![Liq::from_pa(Price(n(9.0)), Amount(n(1.0)))],
+            bids: vec![Liq::from_pa(Price(n(10.0)), Amount(n(1.0)))],
+        };
+        assert!(Spread::from_depth(&d).is_some());
+        assert!(matches!(
+            Spread::from_depth_checked(&d),
+            Err(SpreadError::CrossedDepth(..))
+        ));
+        assert!(matches!(
+            Spread::from_depth_checked(&Depth::new()),
+            Err(SpreadError::EmptyDepth)
+        ));
+    }
+
+    #[test]
+    fn crossed_spread_from_json() {
+        let s = Spread::new(
+            Liq::from_pa(Price(n(9.0)), Amount(n(1.0))),
+            Liq::from_pa(Price(n(10.0)), Amount(n(1.0))),
+        );
+        let s2: Spread = serde_json::from_value(json!(s)).unwrap();
+        assert_eq!(s2.delta_abs(), Price(n(-1.0)));
+    }
 }
@@ -18,6 +18,15 @@ impl Symbol {
             qt: cq.to_uppercase(),
         }
     }
+
+    /// Inverse symbol: quote/base
+    pub fn invert(&self) -> Self {
+        Self {
+            eg: self.eg.clone(),
+            bs: self.qt.clone(),
+            qt: self.bs.clone(),
+        }
+    }
 }
 
 impl std::fmt::Display for Symbol {
@@ -30,6 +39,10 @@ impl std::fmt::Display for Symbol {
 pub enum SymbolError {
     #[error("bad symbol {0}")]
     BadSymbol(String),
+    #[error("unknown quote currency of symbol {0}")]
+    UnknownQuote(String),
+    #[error("no common currency of symbols {0} and {1}")]
+    NoCommonCurrency(String, String),
 }
 
 impl TryFrom<(Exchange, &str)> for Symbol {
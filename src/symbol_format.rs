@@ -0,0 +1,151 @@
+use crate::{Currency, Exchange, Symbol, SymbolError};
+
+/// Known quote currencies to split concatenated symbols like "BTCUSDT".
+/// The longest matching suffix wins, so "USDT" is tried before "USD"
+pub const KNOWN_QUOTES: &[&str] = &[
+    "USDT", "USDC", "BUSD", "FDUSD", "TUSD", "DAI", "USD", "EUR", "GBP", "JPY", "TRY", "BRL",
+    "BTC", "ETH", "BNB",
+];
+
+// Kraken legacy asset codes have X (crypto) or Z (fiat) prefix: XXBTZUSD
+const KRAKEN_FIAT: &[&str] = &["USD", "EUR", "GBP", "JPY", "CAD", "CHF", "AUD"];
+
+/// Exchange-native symbol format: "BTCUSDT", "BTC-USD", "tBTCUSD", "XXBTZUSD"
+pub trait SymbolFormat {
+    fn parse(&self, eg: Exchange, text: &str) -> Result<Symbol, SymbolError>;
+    fn render(&self, symbol: &Symbol) -> String;
+}
+
+/// Built-in formats of the major venues
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VenueFormat {
+    Binance,  // BTCUSDT
+    Bybit,    // BTCUSDT
+    Coinbase, // BTC-USD
+    Okx,      // BTC-USDT
+    Kraken,   // XXBTZUSD (REST), also accepts XBTUSD
+    KrakenWs, // XBT/USD
+    Bitfinex, // tBTCUSD, tTESTBTC:TESTUSD
+}
+
+impl SymbolFormat for VenueFormat {
+    fn parse(&self, eg: Exchange, text: &str) -> Result<Symbol, SymbolError> {
+        match self {
+            VenueFormat::Binance | VenueFormat::Bybit => parse_concat(eg, text),
+            VenueFormat::Coinbase | VenueFormat::Okx => parse_separated(eg, text, '-'),
+            VenueFormat::KrakenWs => parse_separated(eg, text, '/'),
+            VenueFormat::Kraken => parse_kraken(eg, text),
+            VenueFormat::Bitfinex => parse_bitfinex(eg, text),
+        }
+    }
+
+    fn render(&self, symbol: &Symbol) -> String {
+        let (bs, qt) = (&symbol.bs, &symbol.qt);
+        match self {
+            VenueFormat::Binance | VenueFormat::Bybit => format!("{}{}", bs, qt),
+            VenueFormat::Coinbase | VenueFormat::Okx => format!("{}-{}", bs, qt),
+            VenueFormat::KrakenWs => format!("{}/{}", bs, qt),
+            VenueFormat::Kraken => render_kraken(bs, qt),
+            VenueFormat::Bitfinex if bs.len() > 3 || qt.len() > 3 => format!("t{}:{}", bs, qt),
+            VenueFormat::Bitfinex => format!("t{}{}", bs, qt),
+        }
+    }
+}
+
+/// Splits concatenated symbol by known quote currencies
+pub fn split_by_quotes<'a>(text: &'a str, quotes: &[&str]) -> Option<(&'a str, &'a str)> {
+    quotes
+        .iter()
+        .filter(|q| text.len() > q.len() && text.is_char_boundary(text.len() - q.len()))
+        .filter(|q| text[text.len() - q.len()..].eq_ignore_ascii_case(q))
+        .max_by_key(|q| q.len())
+        .map(|q| text.split_at(text.len() - q.len()))
+}
+
+fn new_symbol(eg: Exchange, bs: &str, qt: &str) -> Symbol {
+    Symbol::new(eg, Currency::from(bs), Currency::from(qt))
+}
+
+fn parse_concat(eg: Exchange, text: &str) -> Result<Symbol, SymbolError> {
+    let (bs, qt) = split_by_quotes(text, KNOWN_QUOTES)
+        .ok_or_else(|| SymbolError::UnknownQuote(text.to_string()))?;
+    Ok(new_symbol(eg, bs, qt))
+}
+
+fn parse_separated(eg: Exchange, text: &str, sep: char) -> Result<Symbol, SymbolError> {
+    match text.split_once(sep) {
+        Some((bs, qt)) if !bs.is_empty() && !qt.is_empty() && !qt.contains(sep) => {
+            Ok(new_symbol(eg, bs, qt))
+        }
+        _ => Err(SymbolError::BadSymbol(text.to_string())),
+    }
+}
+
+fn parse_kraken(eg: Exchange, text: &str) -> Result<Symbol, SymbolError> {
+    let b = text.as_bytes();
+    if text.is_ascii() && b.len() == 8 && b[0] == b'X' && (b[4] == b'X' || b[4] == b'Z') {
+        return Ok(new_symbol(eg, &text[1..4], &text[5..]));
+    }
+    parse_concat(eg, text)
+}
+
+fn render_kraken(bs: &str, qt: &str) -> String {
+    if bs.len() != 3 || qt.len() != 3 {
+        return format!("{}{}", bs, qt);
+    }
+    let qt_prefix = if KRAKEN_FIAT.contains(&qt) { 'Z' } else { 'X' };
+    format!("X{}{}{}", bs, qt_prefix, qt)
+}
+
+fn parse_bitfinex(eg: Exchange, text: &str) -> Result<Symbol, SymbolError> {
+    let text_wo_prefix = text
+        .strip_prefix('t')
+        .ok_or_else(|| SymbolError::BadSymbol(text.to_string()))?;
+    if text_wo_prefix.contains(':') {
+        return parse_separated(eg, text_wo_prefix, ':');
+    }
+    if text_wo_prefix.is_ascii() && text_wo_prefix.len() == 6 {
+        let (bs, qt) = text_wo_prefix.split_at(3);
+        return Ok(new_symbol(eg, bs, qt));
+    }
+    parse_concat(eg, text_wo_prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(f: VenueFormat, text: &str, expected: &str) {
+        let s = f.parse("eg".into(), text).unwrap();
+        assert_eq!(s.to_string(), expected);
+        assert_eq!(f.render(&s), text);
+    }
+
+    #[test]
+    fn test_symbol_formats() {
+        round_trip(VenueFormat::Binance, "BTCUSDT", "eg:BTC/USDT");
+        round_trip(VenueFormat::Binance, "ETHBTC", "eg:ETH/BTC");
+        round_trip(VenueFormat::Coinbase, "BTC-USD", "eg:BTC/USD");
+        round_trip(VenueFormat::KrakenWs, "XBT/USD", "eg:XBT/USD");
+        round_trip(VenueFormat::Kraken, "XXBTZUSD", "eg:XBT/USD");
+        round_trip(VenueFormat::Kraken, "XETHXXBT", "eg:ETH/XBT");
+        round_trip(VenueFormat::Bitfinex, "tBTCUSD", "eg:BTC/USD");
+        round_trip(
+            VenueFormat::Bitfinex,
+            "tTESTBTC:TESTUSD",
+            "eg:TESTBTC/TESTUSD",
+        );
+    }
+
+    #[test]
+    fn test_symbol_format_errors() {
+        let r = VenueFormat::Binance.parse("eg".into(), "BTCXYZ");
+        assert!(matches!(r, Err(SymbolError::UnknownQuote(_))));
+        let r = VenueFormat::Coinbase.parse("eg".into(), "BTCUSD");
+        assert!(matches!(r, Err(SymbolError::BadSymbol(_))));
+        let r = VenueFormat::Binance.parse("eg".into(), "USDT");
+        assert!(r.is_err());
+        let r = VenueFormat::Kraken.parse("eg".into(), "XÄBZUSD");
+        assert_eq!(r.unwrap().bs, "XÄBZ");
+    }
+}
@@ -8,8 +8,21 @@ use super::*;
 pub struct Trade {
     pub id: String,
     pub symbol: Symbol,
+    // derivative kind of the symbol, none for spot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<InstrumentKind>,
     pub liq: Liq,
     pub side: Side,
     #[serde(with = "my_date_formatter")]
     pub ts: DateTime<Utc>,
 }
+
+impl Trade {
+    /// Instrument of the symbol and kind, spot if the kind is none
+    pub fn instrument(&self) -> Instrument {
+        Instrument {
+            symbol: self.symbol.clone(),
+            kind: self.kind.clone().unwrap_or(InstrumentKind::Spot),
+        }
+    }
+}
@@ -1,17 +1,31 @@
 use core::ops;
-use std::cmp::{Eq, PartialEq, PartialOrd};
-// use std::cmp::{Ord, Ordering};
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-use crate::{Amount, Price};
+use crate::{
+    check_f64, check_num, deserialize_num, num_cmp, Amount, Num, NumError, Price, NUM_ZERO,
+};
 
 /// Worth (cost) = price * amount
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialOrd, PartialEq)]
-pub struct Worth(pub f64);
+#[derive(Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct Worth(pub Num);
 
 impl Eq for Worth {}
 
+// for min, max, sort and BTreeMap keys
+impl Ord for Worth {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        num_cmp(self.0, rhs.0)
+    }
+}
+
+impl PartialOrd for Worth {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
 impl From<(Price, Amount)> for Worth {
     fn from((p, a): (Price, Amount)) -> Self {
         Self::from_pa(p, a)
@@ -19,11 +33,41 @@ impl From<(Price, Amount)> for Worth {
 }
 
 impl Worth {
+    pub const ZERO: Self = Self(NUM_ZERO);
+
+    /// Checked constructor: NaN, inf and negative values are not allowed
+    pub fn new(v: Num) -> Result<Self, NumError> {
+        check_num(v).map(Self)
+    }
+
     pub fn from_pa(p: Price, a: Amount) -> Self {
         Worth(p.0 * a.0)
     }
 }
 
+impl From<Worth> for Num {
+    fn from(v: Worth) -> Num {
+        v.0
+    }
+}
+
+impl TryFrom<f64> for Worth {
+    type Error = NumError;
+
+    fn try_from(v: f64) -> Result<Self, NumError> {
+        check_f64(v).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Worth {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Self::new(deserialize_num(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
 impl ops::Sub<Worth> for Worth {
     type Output = Self;
     fn sub(self, rhs: Worth) -> Self {
@@ -50,27 +94,27 @@ impl ops::SubAssign<Worth> for Worth {
     }
 }
 
-impl ops::Mul<f64> for Worth {
+impl ops::Mul<Num> for Worth {
     type Output = Self;
-    fn mul(self, rhs: f64) -> Self {
+    fn mul(self, rhs: Num) -> Self {
         Worth(self.0 * rhs)
     }
 }
 
-impl ops::MulAssign<f64> for Worth {
-    fn mul_assign(&mut self, rhs: f64) {
+impl ops::MulAssign<Num> for Worth {
+    fn mul_assign(&mut self, rhs: Num) {
         self.0 = self.0 * rhs;
     }
 }
 
-impl ops::Div<f64> for Worth {
+impl ops::Div<Num> for Worth {
     type Output = Self;
-    fn div(self, rhs: f64) -> Self {
+    fn div(self, rhs: Num) -> Self {
         Worth(self.0 / rhs)
     }
 }
 
-impl ops::Div<Worth> for f64 {
+impl ops::Div<Worth> for Num {
     type Output = Worth;
     fn div(self, rhs: Worth) -> Worth {
         Worth(self / rhs.0)
@@ -78,33 +122,38 @@ impl ops::Div<Worth> for f64 {
 }
 
 impl ops::Div<Worth> for Worth {
-    type Output = f64;
-    fn div(self, rhs: Worth) -> f64 {
+    type Output = Num;
+    fn div(self, rhs: Worth) -> Num {
         self.0 / rhs.0
     }
 }
 
-// for min, max
-// https://www.reddit.com/r/rust/comments/29kia3/no_ord_for_f32/
-// impl Ord for Worth {
-//     fn cmp(&self, rhs: &Self) -> Ordering {
-//         // handle infinity
-//         self.0.partial_cmp(&rhs.0).unwrap_or(Ordering::Equal)
-//     }
-// }
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::n;
 
     #[test]
     fn test_worth() {
-        assert_eq!(Worth(0.5).0, 0.5);
+        assert_eq!(Worth(n(0.5)).0, n(0.5));
     }
 
     #[test]
     fn test_json_to_worth() {
         let w: Worth = serde_json::from_str(r#"1.0"#).unwrap();
-        assert_eq!(w, Worth(1.0));
+        assert_eq!(w, Worth(n(1.0)));
+    }
+
+    #[test]
+    fn test_worth_max_min() {
+        let ww = [Worth(n(2.0)), Worth(n(3.0)), Worth(n(1.0))];
+        assert_eq!(ww.iter().max(), Some(&Worth(n(3.0))));
+        assert_eq!(ww.iter().min(), Some(&Worth(n(1.0))));
+    }
+
+    #[test]
+    fn test_worth_zero_ord() {
+        assert_eq!(Worth(n(-0.0)).cmp(&Worth(n(0.0))), Ordering::Equal);
+        assert!(Worth(n(0.0)) < Worth(n(0.1)));
     }
 }